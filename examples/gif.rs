@@ -0,0 +1,62 @@
+// Copyright Claudio Mattera 2021.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Render the evolution of a pattern to an animated GIF
+//!
+//! ~~~~bash
+//! cargo run --release --target=x86_64-unknown-linux-gnu --features std \
+//!     --example gif -- PATH OUTPUT [GENERATIONS] [PALETTE]
+//! ~~~~
+//!
+//! The pattern is read as RLE and placed in the centre of the world, and it
+//! evolves with the rule declared in the file on the torus.
+//! Generations default to 60, and the palette defaults to Ice Cream.
+
+use std::env::args;
+use std::fs::{read_to_string, File};
+use std::io::BufWriter;
+
+use game_of_life::format::rle;
+use game_of_life::game::{Rule, Topology};
+use game_of_life::graphics::gif::write_run;
+use game_of_life::graphics::Palette;
+
+/// Delay between generations, in hundredths of a second
+const DELAY: u16 = 10;
+
+fn main() {
+    let path = args().nth(1).expect("Missing path");
+    let output = args().nth(2).expect("Missing output path");
+    let generations = args()
+        .nth(3)
+        .map(|value| value.parse().expect("Invalid number of generations"))
+        .unwrap_or(60);
+    let palette = match args().nth(4) {
+        Some(name) => Palette::ALL
+            .iter()
+            .copied()
+            .find(|palette| palette.name().eq_ignore_ascii_case(&name))
+            .unwrap_or_else(|| panic!("Unknown palette {}", name)),
+        None => Palette::IceCream,
+    };
+
+    let input = read_to_string(&path).expect("Cannot read file");
+    let pattern = rle::parse(&input).unwrap_or_else(|error| panic!("{}", error));
+    let rule = pattern.rule.unwrap_or(Rule::CONWAY);
+
+    let file = BufWriter::new(File::create(&output).expect("Cannot create file"));
+    write_run(
+        file,
+        &pattern.world(),
+        rule,
+        Topology::Torus,
+        generations,
+        &palette,
+        DELAY,
+    )
+    .expect("Cannot write GIF");
+    eprintln!("Wrote {} frames to {}", generations + 1, output);
+}
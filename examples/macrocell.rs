@@ -0,0 +1,51 @@
+// Copyright Claudio Mattera 2021.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Convert patterns between Macrocell and RLE formats
+//!
+//! ~~~~bash
+//! cargo run --release --target=x86_64-unknown-linux-gnu --features std \
+//!     --example macrocell -- PATH
+//! ~~~~
+//!
+//! Macrocell files (`.mc`) are cropped around their live cells to the size of
+//! the world and written as RLE, other files are read as RLE and written as
+//! Macrocell.
+
+use std::env::args;
+use std::fs::read_to_string;
+
+use game_of_life::format::{macrocell, rle, Metadata};
+use game_of_life::game::Rule;
+
+fn main() {
+    let path = args().nth(1).expect("Missing path");
+    let input = read_to_string(&path).expect("Cannot read file");
+
+    let mut output = String::new();
+    if path.ends_with(".mc") {
+        let pattern = macrocell::parse(&input).unwrap_or_else(|error| panic!("{}", error));
+        eprintln!(
+            "Read {} live cells in a square of side 2^{}",
+            pattern.population(),
+            pattern.level(),
+        );
+        let world = pattern.crop_centre();
+        let rule = pattern.rule.unwrap_or(Rule::CONWAY);
+        let mut metadata = Metadata::new();
+        for comment in &pattern.comments {
+            metadata.push_comment(comment);
+        }
+        rle::write_world(&mut output, &world, rule, &metadata).expect("Cannot write RLE");
+    } else {
+        let pattern = rle::parse(&input).unwrap_or_else(|error| panic!("{}", error));
+        let mut converted = macrocell::Macrocell::from_world(&pattern.world());
+        converted.rule = pattern.rule;
+        converted.comments = pattern.metadata.comments().map(String::from).collect();
+        macrocell::write(&mut output, &converted).expect("Cannot write Macrocell");
+    }
+    print!("{}", output);
+}
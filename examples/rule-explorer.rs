@@ -0,0 +1,57 @@
+// Copyright Claudio Mattera 2021.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Classify the behaviour of Life-like rules and export it as CSV
+//!
+//! ~~~~bash
+//! cargo run --release --target=x86_64-unknown-linux-gnu --features std \
+//!     --example rule-explorer -- [RULES] [SEED] [FIRST RULE | random]
+//! ~~~~
+//!
+//! Rules are explored in order starting from the first rule, by default
+//! B3/S23, or sampled at random.
+
+use std::env::args;
+
+use game_of_life::analysis::{Behaviour, Explorer, Order, Random};
+use game_of_life::game::Rule;
+
+fn main() {
+    let mut arguments = args().skip(1);
+    let rules: u32 = arguments
+        .next()
+        .map(|argument| argument.parse().expect("Invalid number of rules"))
+        .unwrap_or(100);
+    let seed: u64 = arguments
+        .next()
+        .map(|argument| argument.parse().expect("Invalid seed"))
+        .unwrap_or(0);
+    let order = match arguments.next() {
+        Some(argument) if argument == "random" => Order::Random(Random::new(seed)),
+        Some(argument) => Order::Sequential(Rule::parse(&argument).expect("Invalid rule").index()),
+        None => Order::Sequential(Rule::CONWAY.index()),
+    };
+    let mut explorer = Explorer::new(order, seed);
+
+    println!("rule,behaviour,period,generations,min_population,max_population,final_population");
+    for _ in 0..rules {
+        let sample = explorer.explore();
+        let period = match sample.behaviour {
+            Behaviour::Periodic(period) => period,
+            _ => 0,
+        };
+        println!(
+            "{},{},{},{},{},{},{}",
+            sample.rule,
+            sample.behaviour.name(),
+            period,
+            sample.generations,
+            sample.min_population,
+            sample.max_population,
+            sample.final_population,
+        );
+    }
+}
@@ -0,0 +1,94 @@
+// Copyright Claudio Mattera 2021.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Search for objects in random soups
+//!
+//! ~~~~bash
+//! cargo run --release --target=x86_64-unknown-linux-gnu --features std \
+//!     --example soup-search -- [SOUPS] [FIRST SEED]
+//! ~~~~
+
+use std::cmp::Reverse;
+use std::env::args;
+
+use game_of_life::analysis::{Class, Search, Tally};
+
+/// Objects found at most this many times are reported as rare
+const RARE_THRESHOLD: u64 = 2;
+
+fn main() {
+    let mut arguments = args().skip(1);
+    let soups: u64 = arguments
+        .next()
+        .map(|argument| argument.parse().expect("Invalid number of soups"))
+        .unwrap_or(1000);
+    let first_seed: u64 = arguments
+        .next()
+        .map(|argument| argument.parse().expect("Invalid seed"))
+        .unwrap_or(0);
+
+    let mut search = Search::new();
+    for seed in first_seed..(first_seed + soups) {
+        search.run(seed);
+    }
+
+    println!(
+        "Searched {} soups, {} did not stabilise",
+        search.soups(),
+        search.unstable()
+    );
+    println!();
+
+    let mut tallies: Vec<&Tally> = search.tallies().collect();
+    tallies.sort_by_key(|tally| Reverse(tally.count));
+    for tally in &tallies {
+        println!(
+            "{:>8}  {:<16} {:<24} {:>3} cells  {}",
+            tally.count,
+            tally.name.unwrap_or("-"),
+            describe(&tally.class),
+            tally.population,
+            apgcode(tally),
+        );
+    }
+
+    println!();
+    println!("Rare finds:");
+    for tally in search.rare(RARE_THRESHOLD) {
+        println!(
+            "  {} {} ({}) in soup {}",
+            tally.name.unwrap_or("unnamed"),
+            describe(&tally.class),
+            apgcode(tally),
+            tally.seed,
+        );
+    }
+}
+
+fn describe(class: &Class) -> String {
+    match class {
+        Class::StillLife => "still life".to_owned(),
+        Class::Oscillator { period } => format!("oscillator p{}", period),
+        Class::Spaceship(velocity) => {
+            let (numerator, denominator) = velocity.speed();
+            format!(
+                "spaceship {}c/{} {}",
+                numerator,
+                denominator,
+                velocity.direction().name()
+            )
+        }
+        Class::Unknown => "unknown".to_owned(),
+    }
+}
+
+/// Format the apgcode of an object, or its hash when it has none
+fn apgcode(tally: &Tally) -> String {
+    match tally.apgcode {
+        Some(apgcode) => apgcode.to_string(),
+        None => format!("hash {:016x}", tally.hash),
+    }
+}
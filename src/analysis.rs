@@ -0,0 +1,31 @@
+// Copyright Claudio Mattera 2021.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Analysis of worlds and their evolution
+
+mod activity;
+pub use activity::ActivityMap;
+
+mod census;
+pub use census::{Census, Class, Object, ObjectCounter};
+
+mod cycle;
+pub use cycle::{Cycle, CycleDetector};
+
+mod explorer;
+pub use explorer::{Behaviour, Explorer, Order, Sample, Trial};
+
+mod predecessor;
+pub use predecessor::{find_predecessor, Predecessor, MAX_SEARCH_CELLS};
+
+mod search;
+pub use search::{soup, soup_into, stabilise, Random, Search, Tally, SOUP_SIZE};
+
+mod statistics;
+pub use statistics::{block_entropy, Statistics, Symmetry, BLOCK_SIZES};
+
+mod velocity;
+pub use velocity::{Direction, Velocity};
@@ -0,0 +1,169 @@
+// Copyright Claudio Mattera 2021.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Cell activity over time
+
+use crate::game::World;
+
+const CELLS: usize = World::WIDTH * World::HEIGHT;
+const WORDS: usize = CELLS / 64 + 1;
+
+/// A map of how often each cell changed state
+///
+/// Changes are counted over a sliding window of the most recent
+/// [`ActivityMap::WINDOW`] generations.
+#[derive(Debug)]
+pub struct ActivityMap {
+    counts: [u8; CELLS],
+    history: [[u64; WORDS]; ActivityMap::WINDOW],
+    next: usize,
+}
+
+impl ActivityMap {
+    pub const WINDOW: usize = 32;
+
+    pub const fn new() -> Self {
+        Self {
+            counts: [0; CELLS],
+            history: [[0; WORDS]; Self::WINDOW],
+            next: 0,
+        }
+    }
+
+    /// Forget all recorded changes
+    pub fn reset(&mut self) {
+        for count in self.counts.iter_mut() {
+            *count = 0;
+        }
+        for words in self.history.iter_mut() {
+            *words = [0; WORDS];
+        }
+        self.next = 0;
+    }
+
+    /// Record the changes between two consecutive generations
+    pub fn record(&mut self, previous: &World, current: &World) {
+        let oldest = &mut self.history[self.next];
+        for (i, count) in self.counts.iter_mut().enumerate() {
+            if oldest[i / 64] & (1 << (i % 64)) != 0 {
+                *count -= 1;
+            }
+        }
+        *oldest = [0; WORDS];
+
+        for y in 0..(World::HEIGHT as i16) {
+            for x in 0..(World::WIDTH as i16) {
+                if previous.get_cell(x, y) != current.get_cell(x, y) {
+                    let i = y as usize * World::WIDTH + x as usize;
+                    oldest[i / 64] |= 1 << (i % 64);
+                    self.counts[i] += 1;
+                }
+            }
+        }
+
+        self.next = (self.next + 1) % Self::WINDOW;
+    }
+
+    /// Get the number of changes of a cell within the window
+    pub fn activity(&self, x: i16, y: i16) -> u8 {
+        self.counts[y as usize * World::WIDTH + x as usize]
+    }
+
+    /// Get the activity level of a cell, from 0 (no changes) to 3 (changing
+    /// in most generations)
+    pub fn level(&self, x: i16, y: i16) -> u8 {
+        match self.activity(x, y) as usize {
+            0 => 0,
+            count if count <= Self::WINDOW / 8 => 1,
+            count if count <= Self::WINDOW / 2 => 2,
+            _ => 3,
+        }
+    }
+}
+
+impl Default for ActivityMap {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn blinker() -> World {
+        let mut world = World::new();
+        world.set_cell(8, 7, true);
+        world.set_cell(8, 8, true);
+        world.set_cell(8, 9, true);
+        world
+    }
+
+    #[test]
+    fn count_changes() {
+        let mut world = blinker();
+        let mut activity = ActivityMap::new();
+
+        for _ in 0..4 {
+            let previous = world.clone();
+            world.step();
+            activity.record(&previous, &world);
+        }
+
+        assert_eq!(activity.activity(8, 7), 4);
+        assert_eq!(activity.activity(7, 8), 4);
+        assert_eq!(activity.activity(8, 8), 0);
+        assert_eq!(activity.activity(0, 0), 0);
+    }
+
+    #[test]
+    fn forget_changes_outside_the_window() {
+        let mut world = blinker();
+        let mut activity = ActivityMap::new();
+
+        let previous = world.clone();
+        world.step();
+        activity.record(&previous, &world);
+        for _ in 0..ActivityMap::WINDOW {
+            activity.record(&world, &world);
+        }
+
+        assert_eq!(activity.activity(8, 7), 0);
+        assert_eq!(activity.level(8, 7), 0);
+    }
+
+    #[test]
+    fn levels() {
+        let mut world = blinker();
+        let mut activity = ActivityMap::new();
+
+        for _ in 0..ActivityMap::WINDOW {
+            let previous = world.clone();
+            world.step();
+            activity.record(&previous, &world);
+        }
+
+        assert_eq!(activity.level(8, 7), 3);
+        assert_eq!(activity.level(8, 8), 0);
+    }
+
+    #[test]
+    fn reset_forgets_changes() {
+        let mut world = blinker();
+        let mut activity = ActivityMap::new();
+
+        for _ in 0..3 {
+            let previous = world.clone();
+            world.step();
+            activity.record(&previous, &world);
+        }
+        activity.reset();
+        activity.record(&world, &world);
+
+        assert_eq!(activity.activity(8, 7), 0);
+        assert_eq!(activity.activity(7, 8), 0);
+    }
+}
@@ -0,0 +1,632 @@
+// Copyright Claudio Mattera 2021.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Census of objects in a world
+
+use crate::game::{Pattern, Region, Rule, World};
+
+use super::velocity::wrap;
+use super::Velocity;
+
+/// Class of an object
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Class {
+    /// An object that never changes
+    StillLife,
+
+    /// An object that repeats itself in place with the given period
+    Oscillator { period: usize },
+
+    /// An object that repeats itself after moving
+    Spaceship(Velocity),
+
+    /// An object that does not repeat itself when evolved in isolation
+    Unknown,
+}
+
+/// An object found by a census
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Object {
+    pub class: Class,
+    pub name: Option<&'static str>,
+
+    /// Smallest canonical hash among all phases of the object
+    pub hash: u64,
+
+    pub population: usize,
+    pub x: i16,
+    pub y: i16,
+    pub width: usize,
+    pub height: usize,
+}
+
+/// A census of the objects in a world
+///
+/// Live cells are grouped in clusters when they are at most two cells apart,
+/// i.e. when they could influence each other in the next generation.
+/// Each cluster is then evolved in isolation to find out whether it is a
+/// still life, an oscillator or a spaceship.
+/// Finally, a cluster is split into objects evolving independently of each
+/// other, so that e.g. two blocks side by side are two objects rather than a
+/// pseudo still life.
+#[derive(Debug)]
+pub struct Census {
+    objects: [Option<Object>; Census::CAPACITY],
+    length: usize,
+}
+
+impl Census {
+    /// Maximal number of objects in a census
+    pub const CAPACITY: usize = 48;
+
+    /// Maximal period of oscillators and spaceships
+    pub const MAX_PERIOD: usize = 30;
+
+    /// Take the census of a world
+    pub fn take(world: &World) -> Self {
+        Self::take_with(world, |_, _| {})
+    }
+
+    /// Take the census of a world, calling a function on each object and a
+    /// world containing it alone
+    pub fn take_with<F>(world: &World, mut visit: F) -> Self
+    where
+        F: FnMut(&Object, &World),
+    {
+        let mut census = Self {
+            objects: [None; Self::CAPACITY],
+            length: 0,
+        };
+
+        let mut counter = ObjectCounter::new();
+        let count = counter.count(world);
+        let mut parts = ObjectCounter::new();
+
+        for label in 1..=count {
+            let cluster = isolate(&counter.labels, label as u8);
+            let object = classify(&cluster);
+            let generations = match object.class {
+                Class::StillLife => 1,
+                Class::Oscillator { period } => period,
+                Class::Spaceship(velocity) => velocity.period,
+                Class::Unknown => 0,
+            };
+
+            // Clusters are only split into objects which can all be classified,
+            // since a part dying out may still be needed for the others to
+            // repeat themselves
+            let part_count = match split(&cluster, generations, &mut parts) {
+                count if count > 1 && (1..=count).all(|part| known(&parts.labels, part)) => count,
+                _ => 1,
+            };
+            for part in 1..=part_count {
+                if census.length == Self::CAPACITY {
+                    return census;
+                }
+
+                let (object, isolated) = if part_count == 1 {
+                    (object, cluster.clone())
+                } else {
+                    let isolated = isolate(&parts.labels, part as u8);
+                    (classify(&isolated), isolated)
+                };
+                visit(&object, &isolated);
+                census.objects[census.length] = Some(object);
+                census.length += 1;
+            }
+        }
+
+        census
+    }
+
+    /// Iterate over all objects
+    pub fn objects(&self) -> impl Iterator<Item = &Object> {
+        self.objects[..self.length].iter().flatten()
+    }
+
+    /// Get the number of objects
+    pub fn len(&self) -> usize {
+        self.length
+    }
+
+    /// Check whether the census contains no objects
+    pub fn is_empty(&self) -> bool {
+        self.length == 0
+    }
+
+    /// Count the objects with a given name
+    pub fn count(&self, name: &str) -> usize {
+        self.objects()
+            .filter(|object| object.name == Some(name))
+            .count()
+    }
+}
+
+/// A counter of the objects in a world
+///
+/// Objects are grouped in clusters as in a census, but are neither isolated,
+/// classified nor split, so a pseudo object counts as one.
+#[derive(Debug)]
+pub struct ObjectCounter {
+    labels: [u8; World::WIDTH * World::HEIGHT],
+    stack: [u16; World::WIDTH * World::HEIGHT],
+}
+
+impl ObjectCounter {
+    pub const fn new() -> Self {
+        Self {
+            labels: [0; World::WIDTH * World::HEIGHT],
+            stack: [0; World::WIDTH * World::HEIGHT],
+        }
+    }
+
+    /// Count the objects in a world
+    ///
+    /// Cells of each object are labelled with its number, starting from one
+    /// in the order of their first cell.
+    pub fn count(&mut self, world: &World) -> usize {
+        self.label(world, 2)
+    }
+
+    /// Label groups of cells at most a given distance apart
+    ///
+    /// Cells of each group are labelled with its number, starting from one
+    /// in the order of their first cell.
+    fn label(&mut self, world: &World, distance: i16) -> usize {
+        for label in self.labels.iter_mut() {
+            *label = 0;
+        }
+
+        let mut label = 0u8;
+        for y in 0..(World::HEIGHT as i16) {
+            for x in 0..(World::WIDTH as i16) {
+                if world.get_cell(x, y) && self.labels[index(x, y)] == 0 {
+                    label += 1;
+                    self.flood_fill(world, x, y, label, distance);
+                }
+            }
+        }
+        label as usize
+    }
+
+    /// Label all cells reachable from a starting cell
+    fn flood_fill(&mut self, world: &World, x: i16, y: i16, label: u8, distance: i16) {
+        let width = World::WIDTH as i16;
+        let height = World::HEIGHT as i16;
+
+        let mut length = 0;
+
+        self.labels[index(x, y)] = label;
+        self.stack[length] = index(x, y) as u16;
+        length += 1;
+
+        while length > 0 {
+            length -= 1;
+            let x = (self.stack[length] as usize % World::WIDTH) as i16;
+            let y = (self.stack[length] as usize / World::WIDTH) as i16;
+
+            for dy in -distance..=distance {
+                for dx in -distance..=distance {
+                    let nx = (x + dx + width) % width;
+                    let ny = (y + dy + height) % height;
+                    if world.get_cell(nx, ny) && self.labels[index(nx, ny)] == 0 {
+                        self.labels[index(nx, ny)] = label;
+                        self.stack[length] = index(nx, ny) as u16;
+                        length += 1;
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl Default for ObjectCounter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Known objects, in one of their phases and orientations
+const KNOWN_OBJECTS: [(&str, &[&str]); 17] = [
+    ("block", &["OO", "OO"]),
+    ("beehive", &[".OO.", "O..O", ".OO."]),
+    ("loaf", &[".OO.", "O..O", ".O.O", "..O."]),
+    ("boat", &["OO.", "O.O", ".O."]),
+    ("ship", &["OO.", "O.O", ".OO"]),
+    ("tub", &[".O.", "O.O", ".O."]),
+    ("pond", &[".OO.", "O..O", "O..O", ".OO."]),
+    ("barge", &[".O..", "O.O.", ".O.O", "..O."]),
+    ("blinker", &["OOO"]),
+    ("toad", &[".OOO", "OOO."]),
+    ("beacon", &["OO..", "OO..", "..OO", "..OO"]),
+    (
+        "pulsar",
+        &[
+            "..OOO...OOO..",
+            ".............",
+            "O....O.O....O",
+            "O....O.O....O",
+            "O....O.O....O",
+            "..OOO...OOO..",
+            ".............",
+            "..OOO...OOO..",
+            "O....O.O....O",
+            "O....O.O....O",
+            "O....O.O....O",
+            ".............",
+            "..OOO...OOO..",
+        ],
+    ),
+    (
+        "pentadecathlon",
+        &["..O....O..", "OO.OOOO.OO", "..O....O.."],
+    ),
+    ("glider", &[".O.", "..O", "OOO"]),
+    ("LWSS", &[".O..O", "O....", "O...O", "OOOO."]),
+    ("MWSS", &["...O..", ".O...O", "O.....", "O....O", "OOOOO."]),
+    (
+        "HWSS",
+        &["...OO..", ".O....O", "O......", "O.....O", "OOOOOO."],
+    ),
+];
+
+fn index(x: i16, y: i16) -> usize {
+    y as usize * World::WIDTH + x as usize
+}
+
+/// Build a world with the cells having a label
+fn isolate(labels: &[u8; World::WIDTH * World::HEIGHT], label: u8) -> World {
+    let mut isolated = World::new();
+    for y in 0..(World::HEIGHT as i16) {
+        for x in 0..(World::WIDTH as i16) {
+            if labels[index(x, y)] == label {
+                isolated.set_cell(x, y, true);
+            }
+        }
+    }
+    isolated
+}
+
+/// Check whether the cells having a label form an object of a known class
+fn known(labels: &[u8; World::WIDTH * World::HEIGHT], label: usize) -> bool {
+    classify(&isolate(labels, label as u8)).class != Class::Unknown
+}
+
+/// Split a cluster into parts evolving independently of each other over a
+/// number of generations
+///
+/// The cluster is first split into groups of touching cells.
+/// Groups are evolved separately and merged whenever a cell would evolve
+/// differently in the whole cluster, until no such cell is left.
+/// Cells of each part are labelled with its number, starting from one, and
+/// the number of parts is returned.
+fn split(cluster: &World, generations: usize, parts: &mut ObjectCounter) -> usize {
+    let width = World::WIDTH as i16;
+    let height = World::HEIGHT as i16;
+
+    let count = parts.label(cluster, 1);
+    if generations == 0 {
+        // Unclassified clusters are kept whole
+        for label in parts.labels.iter_mut().filter(|label| **label != 0) {
+            *label = 1;
+        }
+        return count.min(1);
+    }
+
+    // Part of each group, named after the first group in it
+    let mut part_of = [0u8; 256];
+    for (group, part) in part_of.iter_mut().enumerate() {
+        *part = group as u8;
+    }
+
+    'evolve: loop {
+        let mut cells = [0u8; World::WIDTH * World::HEIGHT];
+        for (cell, group) in cells.iter_mut().zip(parts.labels.iter()) {
+            *cell = part_of[*group as usize];
+        }
+
+        for _ in 0..generations {
+            let mut next = [0u8; World::WIDTH * World::HEIGHT];
+            for y in 0..height {
+                for x in 0..width {
+                    // Parts around the cell, and their live neighbours
+                    let own = cells[index(x, y)];
+                    let mut around = [(own, 0); 9];
+                    let mut length = if own == 0 { 0 } else { 1 };
+                    let mut total = 0;
+                    for dy in -1..=1 {
+                        for dx in -1..=1 {
+                            let neighbour =
+                                cells[index((x + dx + width) % width, (y + dy + height) % height)];
+                            if (dx, dy) == (0, 0) || neighbour == 0 {
+                                continue;
+                            }
+                            total += 1;
+                            match around[..length]
+                                .iter_mut()
+                                .find(|(part, _)| *part == neighbour)
+                            {
+                                Some((_, count)) => *count += 1,
+                                None => {
+                                    around[length] = (neighbour, 1);
+                                    length += 1;
+                                }
+                            }
+                        }
+                    }
+
+                    let together = Rule::CONWAY.next(own != 0, total);
+                    let mut alone = around[..length]
+                        .iter()
+                        .filter(|(part, count)| Rule::CONWAY.next(*part == own, *count));
+                    let part = match (together, alone.next(), alone.next()) {
+                        (false, None, _) => 0,
+                        (true, Some((part, _)), None) => *part,
+                        _ => {
+                            // All parts around the cell interact with each other
+                            let merged = around[..length].iter().map(|(part, _)| *part).min();
+                            for part in part_of.iter_mut() {
+                                if around[..length].iter().any(|(other, _)| other == part) {
+                                    *part = merged.unwrap_or(*part);
+                                }
+                            }
+                            continue 'evolve;
+                        }
+                    };
+                    next[index(x, y)] = part;
+                }
+            }
+            cells = next;
+        }
+        break;
+    }
+
+    // Number parts from one, in the order of their first group
+    let mut number = [0u8; 256];
+    let mut length = 0;
+    for part in part_of[1..=count].iter() {
+        let part = *part as usize;
+        if number[part] == 0 {
+            length += 1;
+            number[part] = length as u8;
+        }
+    }
+    for label in parts.labels.iter_mut().filter(|label| **label != 0) {
+        *label = number[part_of[*label as usize] as usize];
+    }
+    length
+}
+
+/// Evolve an isolated object until it repeats itself
+fn classify(isolated: &World) -> Object {
+    let region = isolated.bounding_box().unwrap_or_else(Region::world);
+    let initial = isolated.extract_pattern(region);
+    let hash = initial.canonical_hash();
+
+    let mut object = Object {
+        class: Class::Unknown,
+        name: name(hash),
+        hash,
+        population: isolated.population(),
+        x: region.x,
+        y: region.y,
+        width: region.width,
+        height: region.height,
+    };
+
+    let mut world = isolated.clone();
+    for period in 1..=Census::MAX_PERIOD {
+        world.step();
+        if world.population() == 0 {
+            break;
+        }
+
+        let new_region = world.bounding_box().unwrap_or_else(Region::world);
+        let pattern = world.extract_pattern(new_region);
+        if pattern == initial {
+            let dx = wrap(new_region.x - region.x, World::WIDTH as i16);
+            let dy = wrap(new_region.y - region.y, World::HEIGHT as i16);
+            object.class = match (period, dx, dy) {
+                (1, _, _) => Class::StillLife,
+                (period, 0, 0) => Class::Oscillator { period },
+                (period, dx, dy) => Class::Spaceship(Velocity::new(period, dx, dy)),
+            };
+            break;
+        }
+
+        let hash = pattern.canonical_hash();
+        object.hash = object.hash.min(hash);
+        if object.name.is_none() {
+            object.name = name(hash);
+        }
+    }
+
+    if object.class == Class::Unknown {
+        object.name = None;
+    }
+
+    object
+}
+
+/// Build a pattern from rows of dead (`.`) and live (`O`) cells
+fn pattern_from_rows(rows: &[&str]) -> Pattern {
+    let width = rows.iter().map(|row| row.len()).max().unwrap_or(0);
+    let mut pattern = Pattern::new(width, rows.len());
+    for (y, row) in rows.iter().enumerate() {
+        for (x, cell) in row.bytes().enumerate() {
+            pattern.set_cell(x, y, cell == b'O');
+        }
+    }
+    pattern
+}
+
+/// Find the name of a pattern among the known objects from its canonical hash
+fn name(hash: u64) -> Option<&'static str> {
+    KNOWN_OBJECTS
+        .iter()
+        .find(|(_, rows)| pattern_from_rows(rows).canonical_hash() == hash)
+        .map(|(name, _)| *name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn place(world: &mut World, x: i16, y: i16, rows: &[&str]) {
+        for (dy, row) in rows.iter().enumerate() {
+            for (dx, cell) in row.bytes().enumerate() {
+                if cell == b'O' {
+                    world.set_cell(x + dx as i16, y + dy as i16, true);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn empty_world() {
+        let world = World::new();
+
+        let census = Census::take(&world);
+
+        assert!(census.is_empty());
+    }
+
+    #[test]
+    fn still_lifes_and_oscillators() {
+        let mut world = World::new();
+        place(&mut world, 1, 1, &["OO", "OO"]);
+        place(&mut world, 8, 1, &[".OO.", "O..O", ".OO."]);
+        place(&mut world, 16, 2, &["OOO"]);
+        place(&mut world, 24, 10, &["O", "O", "O"]);
+
+        let census = Census::take(&world);
+
+        assert_eq!(census.len(), 4);
+        assert_eq!(census.count("block"), 1);
+        assert_eq!(census.count("beehive"), 1);
+        assert_eq!(census.count("blinker"), 2);
+
+        let blinker = census
+            .objects()
+            .find(|object| object.name == Some("blinker"))
+            .unwrap();
+        assert_eq!(blinker.class, Class::Oscillator { period: 2 });
+
+        let block = census
+            .objects()
+            .find(|object| object.name == Some("block"))
+            .unwrap();
+        assert_eq!(block.class, Class::StillLife);
+        assert_eq!((block.x, block.y), (1, 1));
+    }
+
+    #[test]
+    fn phases_share_hash() {
+        let mut world = World::new();
+        place(&mut world, 2, 2, &[".OOO", "OOO."]);
+        place(&mut world, 20, 2, &["..O.", "O..O", "O..O", ".O.."]);
+
+        let census = Census::take(&world);
+        let mut objects = census.objects();
+        let first = objects.next().unwrap();
+        let second = objects.next().unwrap();
+
+        assert_eq!(first.name, Some("toad"));
+        assert_eq!(second.name, Some("toad"));
+        assert_eq!(first.hash, second.hash);
+    }
+
+    #[test]
+    fn glider() {
+        let mut world = World::new();
+        place(&mut world, 7, 6, &[".O.", "..O", "OOO"]);
+
+        let census = Census::take(&world);
+        let object = census.objects().next().unwrap();
+
+        assert_eq!(census.len(), 1);
+        assert_eq!(object.name, Some("glider"));
+        assert_eq!(object.class, Class::Spaceship(Velocity::new(4, 1, 1)));
+    }
+
+    #[test]
+    fn reflected_lightweight_spaceship() {
+        let mut world = World::new();
+        place(&mut world, 10, 6, &["O..O.", "....O", "O...O", ".OOOO"]);
+
+        let census = Census::take(&world);
+        let object = census.objects().next().unwrap();
+
+        assert_eq!(object.name, Some("LWSS"));
+        assert_eq!(object.class, Class::Spaceship(Velocity::new(4, 2, 0)));
+    }
+
+    #[test]
+    fn object_across_the_edges() {
+        let mut world = World::new();
+        place(&mut world, 0, 0, &["O"]);
+        world.set_cell(World::WIDTH as i16 - 1, 0, true);
+        world.set_cell(0, World::HEIGHT as i16 - 1, true);
+        world.set_cell(World::WIDTH as i16 - 1, World::HEIGHT as i16 - 1, true);
+
+        let census = Census::take(&world);
+        let object = census.objects().next().unwrap();
+
+        assert_eq!(census.len(), 1);
+        assert_eq!(object.name, Some("block"));
+        assert_eq!(
+            (object.x, object.y),
+            (World::WIDTH as i16 - 1, World::HEIGHT as i16 - 1)
+        );
+    }
+
+    #[test]
+    fn pseudo_objects() {
+        let mut world = World::new();
+        place(&mut world, 1, 1, &["OO.OO", "OO.OO"]);
+        place(
+            &mut world,
+            20,
+            4,
+            &[
+                "..OOO..", ".......", "O.....O", "O.....O", "O.....O", ".......", "..OOO..",
+            ],
+        );
+
+        let census = Census::take(&world);
+
+        assert_eq!(census.len(), 6);
+        assert_eq!(census.count("block"), 2);
+        assert_eq!(census.count("blinker"), 4);
+    }
+
+    #[test]
+    fn count_objects() {
+        let mut world = World::new();
+        place(&mut world, 1, 1, &["OO", "OO"]);
+        place(&mut world, 4, 2, &["O"]);
+        place(&mut world, 16, 2, &["OOO"]);
+        world.set_cell(World::WIDTH as i16 - 1, World::HEIGHT as i16 - 1, true);
+
+        let mut counter = ObjectCounter::new();
+
+        assert_eq!(counter.count(&world), 2);
+        assert_eq!(counter.count(&World::new()), 0);
+    }
+
+    #[test]
+    fn pulsar() {
+        let mut world = World::new();
+        place(&mut world, 12, 2, KNOWN_OBJECTS[11].1);
+
+        let census = Census::take(&world);
+        let object = census.objects().next().unwrap();
+
+        assert_eq!(census.len(), 1);
+        assert_eq!(object.name, Some("pulsar"));
+        assert_eq!(object.class, Class::Oscillator { period: 3 });
+    }
+}
@@ -0,0 +1,273 @@
+// Copyright Claudio Mattera 2021.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Cycle and stagnation detection
+
+use crate::game::{Region, World};
+
+use super::velocity::wrap;
+use super::Velocity;
+
+/// A repetition found in the evolution of a world
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Cycle {
+    /// All cells are dead
+    Extinct,
+
+    /// The world does not change between generations
+    Static,
+
+    /// The world repeats itself with the given period
+    Periodic(usize),
+
+    /// The world repeats itself after moving, i.e. it contains a single
+    /// spaceship
+    Moving(Velocity),
+}
+
+/// A detector for repeated generations
+///
+/// The detector keeps the hashes of the most recent generations, normalised
+/// to their bounding box, together with the position of the bounding box.
+/// A cycle is reported as soon as a generation matches one of them, either in
+/// the same position or displaced.
+/// Only periods up to [`CycleDetector::HISTORY`] can be detected.
+#[derive(Debug)]
+pub struct CycleDetector {
+    history: [(u64, i16, i16); CycleDetector::HISTORY],
+    length: usize,
+    next: usize,
+}
+
+impl CycleDetector {
+    pub const HISTORY: usize = 32;
+
+    pub const fn new() -> Self {
+        Self {
+            history: [(0, 0, 0); Self::HISTORY],
+            length: 0,
+            next: 0,
+        }
+    }
+
+    /// Forget all previous generations
+    ///
+    /// This function must be called whenever the world is modified outside
+    /// of its normal evolution, e.g. when the user draws on it.
+    pub fn reset(&mut self) {
+        self.length = 0;
+        self.next = 0;
+    }
+
+    /// Record a new generation and check whether it was already seen
+    pub fn observe(&mut self, world: &World) -> Option<Cycle> {
+        let (hash, x, y) = match world.bounding_box() {
+            Some(region) => (world.extract_pattern(region).hash(), region.x, region.y),
+            None => (0, 0, 0),
+        };
+
+        let found = (1..=self.length).find_map(|distance| {
+            let index = (self.next + Self::HISTORY - distance) % Self::HISTORY;
+            let (previous_hash, previous_x, previous_y) = self.history[index];
+            if previous_hash == hash {
+                let dx = wrap(x - previous_x, World::WIDTH as i16);
+                let dy = wrap(y - previous_y, World::HEIGHT as i16);
+                Some(Velocity::new(distance, dx, dy))
+            } else {
+                None
+            }
+        });
+
+        self.history[self.next] = (hash, x, y);
+        self.next = (self.next + 1) % Self::HISTORY;
+        self.length = (self.length + 1).min(Self::HISTORY);
+
+        found.map(
+            |velocity| match (velocity.period, velocity.dx, velocity.dy) {
+                (_, 0, 0) if world.population() == 0 => Cycle::Extinct,
+                (1, 0, 0) => Cycle::Static,
+                (period, 0, 0) => Cycle::Periodic(period),
+                _ => Cycle::Moving(velocity),
+            },
+        )
+    }
+
+    /// Record a new generation of a region of the world and check whether it
+    /// was already seen
+    ///
+    /// Only the cells inside the region are considered, so that a spaceship
+    /// can be detected even when other objects are present elsewhere.
+    pub fn observe_region(&mut self, world: &World, region: Region) -> Option<Cycle> {
+        let mut isolated = World::new();
+        for (x, y) in region.cells() {
+            isolated.set_cell(x, y, world.get_cell(x, y));
+        }
+        self.observe(&isolated)
+    }
+}
+
+impl Default for CycleDetector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(world: &mut World, detector: &mut CycleDetector, generations: usize) -> Option<Cycle> {
+        let mut cycle = detector.observe(world);
+        for _ in 0..generations {
+            if cycle.is_some() {
+                break;
+            }
+            world.step();
+            cycle = detector.observe(world);
+        }
+        cycle
+    }
+
+    #[test]
+    fn extinct() {
+        let mut world = World::new();
+        world.set_cell(8, 8, true);
+        let mut detector = CycleDetector::new();
+
+        let actual = run(&mut world, &mut detector, 10);
+        let expected = Some(Cycle::Extinct);
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn block_is_static() {
+        let mut world = World::new();
+        world.set_cell(8, 8, true);
+        world.set_cell(8, 9, true);
+        world.set_cell(9, 8, true);
+        world.set_cell(9, 9, true);
+        let mut detector = CycleDetector::new();
+
+        let actual = run(&mut world, &mut detector, 10);
+        let expected = Some(Cycle::Static);
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn blinker_is_periodic() {
+        let mut world = World::new();
+        world.set_cell(8, 7, true);
+        world.set_cell(8, 8, true);
+        world.set_cell(8, 9, true);
+        let mut detector = CycleDetector::new();
+
+        let actual = run(&mut world, &mut detector, 10);
+        let expected = Some(Cycle::Periodic(2));
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn glider_is_moving() {
+        let mut world = World::new();
+        world.set_cell(8, 6, true);
+        world.set_cell(9, 7, true);
+        world.set_cell(7, 8, true);
+        world.set_cell(8, 8, true);
+        world.set_cell(9, 8, true);
+        let mut detector = CycleDetector::new();
+
+        let actual = run(&mut world, &mut detector, 10);
+        let expected = Some(Cycle::Moving(Velocity::new(4, 1, 1)));
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn glider_across_the_edges() {
+        let mut world = World::new();
+        world.set_cell(0, 0, true);
+        world.set_cell(1, 1, true);
+        world.set_cell(1, 2, true);
+        world.set_cell(0, 2, true);
+        world.set_cell((World::WIDTH as i16) - 1, 2, true);
+        let mut detector = CycleDetector::new();
+
+        let actual = run(&mut world, &mut detector, 10);
+        let expected = Some(Cycle::Moving(Velocity::new(4, 1, 1)));
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn lightweight_spaceship_is_moving() {
+        let mut world = World::new();
+        for (x, y) in [
+            (1, 0),
+            (4, 0),
+            (0, 1),
+            (0, 2),
+            (4, 2),
+            (0, 3),
+            (1, 3),
+            (2, 3),
+            (3, 3),
+        ] {
+            world.set_cell(10 + x, 6 + y, true);
+        }
+        let mut detector = CycleDetector::new();
+
+        let actual = run(&mut world, &mut detector, 10);
+        let expected = Some(Cycle::Moving(Velocity::new(4, -2, 0)));
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn glider_in_region() {
+        let mut world = World::new();
+        world.set_cell(8, 6, true);
+        world.set_cell(9, 7, true);
+        world.set_cell(7, 8, true);
+        world.set_cell(8, 8, true);
+        world.set_cell(9, 8, true);
+
+        world.set_cell(30, 2, true);
+        world.set_cell(30, 3, true);
+        world.set_cell(31, 2, true);
+        world.set_cell(31, 3, true);
+
+        let region = Region::new(0, 0, 20, World::HEIGHT);
+
+        let mut detector = CycleDetector::new();
+        let mut region_detector = CycleDetector::new();
+        let mut cycle = None;
+        let mut region_cycle = None;
+        for _ in 0..8 {
+            cycle = cycle.or(detector.observe(&world));
+            region_cycle = region_cycle.or(region_detector.observe_region(&world, region));
+            world.step();
+        }
+
+        assert_eq!(cycle, None);
+        assert_eq!(region_cycle, Some(Cycle::Moving(Velocity::new(4, 1, 1))));
+    }
+
+    #[test]
+    fn reset_forgets_history() {
+        let world = World::new();
+        let mut detector = CycleDetector::new();
+        detector.observe(&world);
+        detector.reset();
+
+        let actual = detector.observe(&world);
+        let expected = None;
+
+        assert_eq!(actual, expected);
+    }
+}
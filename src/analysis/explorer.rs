@@ -0,0 +1,364 @@
+// Copyright Claudio Mattera 2021.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Exploration of the space of Life-like rules
+
+use crate::game::{Rule, World};
+
+use super::{soup_into, Cycle, CycleDetector, Random};
+
+/// Behaviour of a soup evolved under a rule
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Behaviour {
+    /// All cells died
+    DiesOut,
+
+    /// The world stopped changing
+    Stable,
+
+    /// The world repeats itself with the given period, possibly after moving
+    Periodic(usize),
+
+    /// The world did not settle, and its population stayed bounded
+    Chaotic,
+
+    /// The world did not settle, and its population grew to fill most of it
+    Explosive,
+}
+
+impl Behaviour {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Behaviour::DiesOut => "dies out",
+            Behaviour::Stable => "stable",
+            Behaviour::Periodic(_) => "periodic",
+            Behaviour::Chaotic => "chaotic",
+            Behaviour::Explosive => "explosive",
+        }
+    }
+}
+
+/// Outcome of evolving a soup under a rule
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Sample {
+    pub rule: Rule,
+    pub behaviour: Behaviour,
+
+    /// Number of generations evolved before the behaviour was decided
+    pub generations: u32,
+
+    pub min_population: usize,
+    pub max_population: usize,
+    pub final_population: usize,
+}
+
+/// A soup being evolved under a rule
+///
+/// The soup is evolved until it repeats itself, or until
+/// [`Trial::MAX_GENERATIONS`] generations have passed.
+/// In the latter case, the behaviour is explosive if the average population
+/// over the last [`Trial::WINDOW`] generations exceeds
+/// [`Trial::EXPLOSIVE_DENSITY`] of the world, and chaotic otherwise.
+#[derive(Debug)]
+pub struct Trial {
+    rule: Rule,
+    world: World,
+    detector: CycleDetector,
+    generation: u32,
+    min_population: usize,
+    max_population: usize,
+    recent_population: usize,
+}
+
+impl Trial {
+    pub const MAX_GENERATIONS: u32 = 512;
+    pub const WINDOW: u32 = 64;
+
+    /// Fraction of live cells above which a soup is explosive, as a
+    /// numerator and a denominator
+    pub const EXPLOSIVE_DENSITY: (usize, usize) = (3, 8);
+
+    /// A trial of an empty world, to be restarted before use
+    const IDLE: Trial = Trial {
+        rule: Rule::CONWAY,
+        world: World::new(),
+        detector: CycleDetector::new(),
+        generation: 0,
+        min_population: 0,
+        max_population: 0,
+        recent_population: 0,
+    };
+
+    pub fn new(rule: Rule, seed: u64) -> Self {
+        let mut trial = Self::IDLE;
+        trial.restart(rule, seed);
+        trial
+    }
+
+    /// Start evolving the soup generated from a seed under a rule again
+    pub fn restart(&mut self, rule: Rule, seed: u64) {
+        soup_into(seed, &mut self.world);
+        let population = self.world.population();
+        self.rule = rule;
+        self.detector.reset();
+        self.generation = 0;
+        self.min_population = population;
+        self.max_population = population;
+        self.recent_population = 0;
+    }
+
+    pub fn rule(&self) -> Rule {
+        self.rule
+    }
+
+    pub fn world(&self) -> &World {
+        &self.world
+    }
+
+    /// Evolve the soup by at most a given number of generations
+    ///
+    /// The sample is returned as soon as the behaviour is decided.
+    pub fn advance(&mut self, generations: u32) -> Option<Sample> {
+        for _ in 0..generations {
+            let population = self.world.population();
+            self.min_population = self.min_population.min(population);
+            self.max_population = self.max_population.max(population);
+            if self.generation > Self::MAX_GENERATIONS - Self::WINDOW {
+                self.recent_population += population;
+            }
+
+            let behaviour = match self.detector.observe(&self.world) {
+                Some(Cycle::Extinct) => Some(Behaviour::DiesOut),
+                Some(Cycle::Static) => Some(Behaviour::Stable),
+                Some(Cycle::Periodic(period)) => Some(Behaviour::Periodic(period)),
+                Some(Cycle::Moving(velocity)) => Some(Behaviour::Periodic(velocity.period)),
+                None if self.generation == Self::MAX_GENERATIONS => Some(self.unsettled()),
+                None => None,
+            };
+            if let Some(behaviour) = behaviour {
+                return Some(self.sample(behaviour));
+            }
+
+            self.world.step_with(self.rule);
+            self.generation += 1;
+        }
+        None
+    }
+
+    /// Evolve the soup until its behaviour is decided
+    pub fn run(&mut self) -> Sample {
+        loop {
+            if let Some(sample) = self.advance(Self::MAX_GENERATIONS) {
+                return sample;
+            }
+        }
+    }
+
+    fn unsettled(&self) -> Behaviour {
+        let (numerator, denominator) = Self::EXPLOSIVE_DENSITY;
+        let cells = World::WIDTH * World::HEIGHT * Self::WINDOW as usize;
+        if self.recent_population * denominator > cells * numerator {
+            Behaviour::Explosive
+        } else {
+            Behaviour::Chaotic
+        }
+    }
+
+    fn sample(&self, behaviour: Behaviour) -> Sample {
+        Sample {
+            rule: self.rule,
+            behaviour,
+            generations: self.generation,
+            min_population: self.min_population,
+            max_population: self.max_population,
+            final_population: self.world.population(),
+        }
+    }
+}
+
+/// Order in which rules are explored
+#[derive(Clone, Debug)]
+pub enum Order {
+    /// Rules are explored by increasing index, starting from the given one
+    Sequential(u32),
+
+    /// Rules are sampled at random
+    Random(Random),
+}
+
+/// An explorer of the space of Life-like rules
+///
+/// The same soup is evolved under every rule, so that samples are comparable.
+#[derive(Debug)]
+pub struct Explorer {
+    order: Order,
+    seed: u64,
+    trial: Trial,
+
+    /// Whether a rule is being explored in the trial
+    exploring: bool,
+}
+
+impl Explorer {
+    /// Create an explorer evolving the soup generated from a seed
+    pub const fn new(order: Order, seed: u64) -> Self {
+        Self {
+            order,
+            seed,
+            trial: Trial::IDLE,
+            exploring: false,
+        }
+    }
+
+    /// Explore rules in another order, abandoning the current rule
+    pub fn restart(&mut self, order: Order) {
+        self.order = order;
+        self.exploring = false;
+    }
+
+    /// Get the rule currently being explored, if any
+    pub fn current(&self) -> Option<Rule> {
+        if self.exploring {
+            Some(self.trial.rule())
+        } else {
+            None
+        }
+    }
+
+    /// Choose the next rule to explore
+    pub fn next_rule(&mut self) -> Rule {
+        match &mut self.order {
+            Order::Sequential(index) => {
+                let rule = Rule::from_index(*index);
+                *index = (*index + 1) % Rule::COUNT;
+                rule
+            }
+            Order::Random(random) => Rule::from_index(random.below(Rule::COUNT as u64) as u32),
+        }
+    }
+
+    /// Evolve the current rule by at most a given number of generations
+    ///
+    /// A new rule is chosen if none is being explored, and the sample is
+    /// returned once its behaviour is decided.
+    pub fn advance(&mut self, generations: u32) -> Option<Sample> {
+        if !self.exploring {
+            let rule = self.next_rule();
+            self.trial.restart(rule, self.seed);
+            self.exploring = true;
+        }
+        let sample = self.trial.advance(generations);
+        if sample.is_some() {
+            self.exploring = false;
+        }
+        sample
+    }
+
+    /// Explore the next rule until its behaviour is decided
+    pub fn explore(&mut self) -> Sample {
+        loop {
+            if let Some(sample) = self.advance(Trial::MAX_GENERATIONS) {
+                return sample;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::soup;
+
+    fn behaviour(rule: &str) -> Behaviour {
+        Trial::new(Rule::parse(rule).unwrap(), 1).run().behaviour
+    }
+
+    #[test]
+    fn dies_out() {
+        assert_eq!(behaviour("B/S"), Behaviour::DiesOut);
+    }
+
+    #[test]
+    fn stable() {
+        assert_eq!(behaviour("B/S012345678"), Behaviour::Stable);
+    }
+
+    #[test]
+    fn periodic() {
+        assert_eq!(behaviour("B3/S23"), Behaviour::Periodic(2));
+    }
+
+    #[test]
+    fn glider_is_periodic() {
+        let mut trial = Trial::new(Rule::CONWAY, 1);
+        trial.world = World::new();
+        for (x, y) in [(5, 5), (6, 6), (4, 7), (5, 7), (6, 7)].iter() {
+            trial.world.set_cell(*x, *y, true);
+        }
+
+        let actual = trial.run().behaviour;
+        let expected = Behaviour::Periodic(4);
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn chaotic() {
+        assert_eq!(behaviour("B2/S"), Behaviour::Chaotic);
+    }
+
+    #[test]
+    fn explosive() {
+        assert_eq!(behaviour("B1357/S1357"), Behaviour::Explosive);
+    }
+
+    #[test]
+    fn window_of_recent_generations() {
+        let mut trial = Trial::new(Rule::parse("B/S012345678").unwrap(), 1);
+        trial.generation = Trial::MAX_GENERATIONS - Trial::WINDOW;
+
+        trial.run();
+
+        assert_eq!(trial.recent_population, soup(1).population());
+    }
+
+    #[test]
+    fn sample_statistics() {
+        let sample = Trial::new(Rule::parse("B/S").unwrap(), 1).run();
+
+        assert_eq!(sample.min_population, 0);
+        assert_eq!(sample.final_population, 0);
+        assert_eq!(sample.max_population, soup(1).population());
+        assert_eq!(sample.generations, 2);
+    }
+
+    #[test]
+    fn sequential_order() {
+        let mut explorer = Explorer::new(Order::Sequential(Rule::CONWAY.index()), 1);
+
+        let first = explorer.explore();
+        let second = explorer.explore();
+
+        assert_eq!(first.rule, Rule::CONWAY);
+        assert_eq!(second.rule.index(), Rule::CONWAY.index() + 1);
+    }
+
+    #[test]
+    fn advance_in_steps() {
+        let mut explorer = Explorer::new(Order::Random(Random::new(3)), 1);
+
+        let mut sample = None;
+        let mut calls = 0;
+        while sample.is_none() {
+            sample = explorer.advance(16);
+            calls += 1;
+        }
+        let sample = sample.unwrap();
+
+        assert!(calls * 16 > sample.generations);
+        assert_eq!(explorer.current(), None);
+    }
+}
@@ -0,0 +1,333 @@
+// Copyright Claudio Mattera 2021.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Search for predecessors of patterns
+
+use crate::game::{Region, Rule, World};
+
+/// Maximal number of cells in the region of a predecessor search
+pub const MAX_SEARCH_CELLS: usize = 64;
+
+/// Maximal number of cells in the region extended by one cell on each side
+const MAX_GRID_CELLS: usize = 256;
+
+/// Outcome of a predecessor search
+#[allow(clippy::large_enum_variant)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Predecessor {
+    /// A parent that evolves into the target
+    Found(World),
+
+    /// No parent exists within the region, i.e. the target is a Garden of
+    /// Eden with respect to the region
+    GardenOfEden,
+
+    /// The search was abandoned, either because the region is too large or
+    /// because too many configurations were tried
+    Unknown,
+}
+
+/// Search for a parent evolving into a target world in one generation
+///
+/// Only parents whose live cells lie inside the region are considered.
+/// The search assigns the cells of the region one at a time, and backtracks
+/// as soon as a cell of the target cannot be obtained anymore from the
+/// assigned cells.
+/// At most `max_nodes` assignments are tried, and the region must contain at
+/// most [`MAX_SEARCH_CELLS`] cells.
+pub fn find_predecessor(target: &World, region: Region, rule: Rule, max_nodes: u64) -> Predecessor {
+    if region.width * region.height > MAX_SEARCH_CELLS
+        || region.width + 2 > World::WIDTH
+        || region.height + 2 > World::HEIGHT
+    {
+        return Predecessor::Unknown;
+    }
+
+    let grid = Grid::new(region);
+
+    // Cells farther than one cell from the region have only dead neighbours
+    let dead_outside = (0..World::HEIGHT as i16)
+        .flat_map(|y| (0..World::WIDTH as i16).map(move |x| (x, y)))
+        .filter(|(x, y)| !grid.region.contains(*x, *y))
+        .all(|(x, y)| target.get_cell(x, y) == rule.next(false, 0));
+    if !dead_outside {
+        return Predecessor::GardenOfEden;
+    }
+
+    // An empty region only allows the empty parent, which the cells next to
+    // the region must also match
+    let cells = region.width * region.height;
+    if cells == 0 {
+        let empty = (0..World::HEIGHT as i16)
+            .flat_map(|y| (0..World::WIDTH as i16).map(move |x| (x, y)))
+            .all(|(x, y)| target.get_cell(x, y) == rule.next(false, 0));
+        return if empty {
+            Predecessor::Found(World::new())
+        } else {
+            Predecessor::GardenOfEden
+        };
+    }
+
+    let mut parent = [State::Dead; MAX_GRID_CELLS];
+    for i in 0..cells {
+        parent[grid.inner(i)] = State::Unknown;
+    }
+
+    let mut tried = [0u8; MAX_SEARCH_CELLS];
+    let mut depth = 0;
+    let mut nodes = 0;
+    loop {
+        let index = grid.inner(depth);
+        if tried[depth] == 2 {
+            tried[depth] = 0;
+            parent[index] = State::Unknown;
+            if depth == 0 {
+                return Predecessor::GardenOfEden;
+            }
+            depth -= 1;
+            continue;
+        }
+
+        nodes += 1;
+        if nodes > max_nodes {
+            return Predecessor::Unknown;
+        }
+
+        parent[index] = if tried[depth] == 0 {
+            State::Dead
+        } else {
+            State::Alive
+        };
+        tried[depth] += 1;
+
+        if grid.consistent(&parent, index, target, rule) {
+            depth += 1;
+            if depth == cells {
+                return Predecessor::Found(grid.world(&parent));
+            }
+        }
+    }
+}
+
+/// State of a cell of the parent during the search
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum State {
+    Dead,
+    Alive,
+    Unknown,
+}
+
+/// The region of a search extended by one cell on each side
+///
+/// Cells of the grid are indexed in row-major order, and map to cells of the
+/// world wrapping around its edges.
+struct Grid {
+    region: Region,
+    inner_width: usize,
+}
+
+impl Grid {
+    fn new(inner: Region) -> Self {
+        Self {
+            region: Region::new(inner.x - 1, inner.y - 1, inner.width + 2, inner.height + 2),
+            inner_width: inner.width,
+        }
+    }
+
+    /// Get the index of the `i`-th cell of the inner region
+    fn inner(&self, i: usize) -> usize {
+        let x = i % self.inner_width + 1;
+        let y = i / self.inner_width + 1;
+        y * self.region.width + x
+    }
+
+    /// Get the world coordinates of a cell of the grid
+    fn coordinates(&self, index: usize) -> (i16, i16) {
+        let x = self.region.x + (index % self.region.width) as i16;
+        let y = self.region.y + (index / self.region.width) as i16;
+        (
+            x.rem_euclid(World::WIDTH as i16),
+            y.rem_euclid(World::HEIGHT as i16),
+        )
+    }
+
+    /// Check whether the target can still be obtained around a cell
+    ///
+    /// Each cell of the target next to the assigned cell is checked against
+    /// the range of live neighbours still possible in the parent.
+    fn consistent(&self, parent: &[State], index: usize, target: &World, rule: Rule) -> bool {
+        let width = self.region.width as isize;
+        let height = self.region.height as isize;
+        let x0 = (index % self.region.width) as isize;
+        let y0 = (index / self.region.width) as isize;
+
+        for y in (y0 - 1)..=(y0 + 1) {
+            for x in (x0 - 1)..=(x0 + 1) {
+                let mut alive = 0;
+                let mut unknown = 0;
+                for dy in -1..=1 {
+                    for dx in -1..=1 {
+                        let (nx, ny) = (x + dx, y + dy);
+                        if (dx, dy) == (0, 0) || nx < 0 || ny < 0 || nx >= width || ny >= height {
+                            continue;
+                        }
+                        match parent[(ny * width + nx) as usize] {
+                            State::Alive => alive += 1,
+                            State::Unknown => unknown += 1,
+                            State::Dead => {}
+                        }
+                    }
+                }
+
+                let cell = (y * width + x) as usize;
+                let centres: &[bool] = match parent[cell] {
+                    State::Dead => &[false],
+                    State::Alive => &[true],
+                    State::Unknown => &[false, true],
+                };
+                let (tx, ty) = self.coordinates(cell);
+                let expected = target.get_cell(tx, ty);
+                let possible = centres.iter().any(|centre| {
+                    (alive..=(alive + unknown)).any(|count| rule.next(*centre, count) == expected)
+                });
+                if !possible {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+
+    /// Create a world with the live cells of the parent
+    fn world(&self, parent: &[State]) -> World {
+        let mut world = World::new();
+        for (index, state) in parent[..self.region.width * self.region.height]
+            .iter()
+            .enumerate()
+        {
+            if *state == State::Alive {
+                let (x, y) = self.coordinates(index);
+                world.set_cell(x, y, true);
+            }
+        }
+        world
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAX_NODES: u64 = 1_000_000;
+
+    fn check(target: &World, region: Region) -> Predecessor {
+        let predecessor = find_predecessor(target, region, Rule::CONWAY, MAX_NODES);
+        if let Predecessor::Found(parent) = &predecessor {
+            let mut child = parent.clone();
+            child.step();
+            assert_eq!(&child, target);
+            assert_eq!(
+                region
+                    .cells()
+                    .filter(|(x, y)| parent.get_cell(*x, *y))
+                    .count(),
+                parent.population()
+            );
+        }
+        predecessor
+    }
+
+    #[test]
+    fn blinker() {
+        let mut target = World::new();
+        target.set_cell(7, 8, true);
+        target.set_cell(8, 8, true);
+        target.set_cell(9, 8, true);
+
+        let predecessor = check(&target, Region::new(7, 7, 3, 3));
+
+        assert!(matches!(predecessor, Predecessor::Found(_)));
+    }
+
+    #[test]
+    fn empty_world() {
+        let target = World::new();
+
+        let actual = check(&target, Region::new(3, 3, 4, 4));
+
+        assert_eq!(actual, Predecessor::Found(World::new()));
+    }
+
+    #[test]
+    fn empty_region() {
+        let mut target = World::new();
+        target.set_cell(10, 10, true);
+
+        assert_eq!(
+            check(&target, Region::new(10, 10, 0, 1)),
+            Predecessor::GardenOfEden
+        );
+        assert_eq!(
+            check(&World::new(), Region::new(10, 10, 1, 0)),
+            Predecessor::Found(World::new())
+        );
+    }
+
+    #[test]
+    fn single_cell() {
+        let mut target = World::new();
+        target.set_cell(10, 10, true);
+
+        assert!(matches!(
+            check(&target, Region::new(9, 9, 3, 3)),
+            Predecessor::Found(_)
+        ));
+        assert_eq!(
+            check(&target, Region::new(10, 10, 1, 1)),
+            Predecessor::GardenOfEden
+        );
+    }
+
+    #[test]
+    fn cell_far_from_region() {
+        let mut target = World::new();
+        target.set_cell(20, 10, true);
+
+        let actual = check(&target, Region::new(2, 2, 3, 3));
+
+        assert_eq!(actual, Predecessor::GardenOfEden);
+    }
+
+    #[test]
+    fn across_the_edges() {
+        let mut target = World::new();
+        target.set_cell(0, 0, true);
+        target.set_cell(World::WIDTH as i16 - 1, 0, true);
+        target.set_cell(0, World::HEIGHT as i16 - 1, true);
+        target.set_cell(World::WIDTH as i16 - 1, World::HEIGHT as i16 - 1, true);
+
+        let region = Region::new(World::WIDTH as i16 - 2, World::HEIGHT as i16 - 2, 4, 4);
+        let predecessor = check(&target, region);
+
+        assert!(matches!(predecessor, Predecessor::Found(_)));
+    }
+
+    #[test]
+    fn give_up() {
+        let mut target = World::new();
+        target.set_cell(7, 8, true);
+        target.set_cell(8, 8, true);
+        target.set_cell(9, 8, true);
+
+        let actual = find_predecessor(&target, Region::new(7, 7, 3, 3), Rule::CONWAY, 3);
+
+        assert_eq!(actual, Predecessor::Unknown);
+        assert_eq!(
+            find_predecessor(&target, Region::world(), Rule::CONWAY, MAX_NODES),
+            Predecessor::Unknown
+        );
+    }
+}
@@ -0,0 +1,328 @@
+// Copyright Claudio Mattera 2021.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Search for objects in random soups
+
+use crate::format::apgcode::{self, Apgcode};
+use crate::game::World;
+
+use super::{Census, Class, CycleDetector, Object};
+
+/// A pseudo-random number generator
+///
+/// This is the SplitMix64 generator, which is small and fast, and good enough
+/// to generate soups.
+#[derive(Clone, Debug)]
+pub struct Random(u64);
+
+impl Random {
+    pub const fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    pub fn next_bool(&mut self) -> bool {
+        self.next_u64() >> 63 != 0
+    }
+
+    /// Generate a number in the range `0..bound`
+    pub fn below(&mut self, bound: u64) -> u64 {
+        self.next_u64() % bound
+    }
+}
+
+/// Side of the square filled by a soup
+pub const SOUP_SIZE: usize = 16;
+
+/// Generate a random soup from a seed
+///
+/// A soup is a square of [`SOUP_SIZE`] cells in the middle of the world, each
+/// cell alive with probability one half.
+pub fn soup(seed: u64) -> World {
+    let mut world = World::new();
+    soup_into(seed, &mut world);
+    world
+}
+
+/// Replace a world with the random soup generated from a seed
+pub fn soup_into(seed: u64, world: &mut World) {
+    let mut random = Random::new(seed);
+    let x0 = (World::WIDTH - SOUP_SIZE) / 2;
+    let y0 = (World::HEIGHT - SOUP_SIZE) / 2;
+    let xs = x0..x0 + SOUP_SIZE;
+    let ys = y0..y0 + SOUP_SIZE;
+    for y in 0..World::HEIGHT {
+        for x in 0..World::WIDTH {
+            let alive = xs.contains(&x) && ys.contains(&y) && random.next_bool();
+            world.set_cell(x as i16, y as i16, alive);
+        }
+    }
+}
+
+/// Evolve a world until it stabilises
+///
+/// A world is stable when it repeats itself, or when all its objects can be
+/// classified.
+/// The number of generations until stabilisation is returned, or `None` if
+/// the world did not stabilise within the given limit.
+pub fn stabilise(world: &mut World, max_generations: u32) -> Option<u32> {
+    let mut detector = CycleDetector::new();
+    for generation in 0..max_generations {
+        if detector.observe(world).is_some() {
+            return Some(generation);
+        }
+        if generation % 64 == 63 {
+            let census = Census::take(world);
+            if census
+                .objects()
+                .all(|object| object.class != Class::Unknown)
+            {
+                return Some(generation);
+            }
+        }
+        world.step();
+    }
+    None
+}
+
+/// Number of occurrences of an object across all soups
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Tally {
+    pub name: Option<&'static str>,
+    pub class: Class,
+    pub hash: u64,
+    pub population: usize,
+    pub count: u64,
+
+    /// Apgcode of the object, unless its class is unknown
+    pub apgcode: Option<Apgcode>,
+
+    /// Seed of the first soup where the object was found
+    pub seed: u64,
+}
+
+/// A search for objects in random soups
+///
+/// Each soup is evolved until it stabilises, and its objects are tallied by
+/// their apgcode, or by their canonical hash when their class is unknown.
+/// At most [`Search::CAPACITY`] different objects are tallied, additional
+/// objects are ignored.
+#[derive(Debug)]
+pub struct Search {
+    soups: u64,
+    unstable: u64,
+    tallies: [Option<Tally>; Search::CAPACITY],
+    length: usize,
+}
+
+impl Search {
+    pub const CAPACITY: usize = 64;
+    pub const MAX_GENERATIONS: u32 = 2000;
+
+    pub const fn new() -> Self {
+        Self {
+            soups: 0,
+            unstable: 0,
+            tallies: [None; Self::CAPACITY],
+            length: 0,
+        }
+    }
+
+    /// Run the soup generated from a seed, returning whether it stabilised
+    pub fn run(&mut self, seed: u64) -> bool {
+        self.soups += 1;
+
+        let mut world = soup(seed);
+        if stabilise(&mut world, Self::MAX_GENERATIONS).is_none() {
+            self.unstable += 1;
+            return false;
+        }
+
+        Census::take_with(&world, |object, isolated| {
+            self.tally(object, isolated, seed)
+        });
+        true
+    }
+
+    /// Get the number of soups searched
+    pub fn soups(&self) -> u64 {
+        self.soups
+    }
+
+    /// Get the number of soups that did not stabilise
+    pub fn unstable(&self) -> u64 {
+        self.unstable
+    }
+
+    pub fn tallies(&self) -> impl Iterator<Item = &Tally> {
+        self.tallies[..self.length].iter().flatten()
+    }
+
+    /// Iterate over objects found at most a given number of times
+    pub fn rare(&self, threshold: u64) -> impl Iterator<Item = &Tally> {
+        self.tallies().filter(move |tally| tally.count <= threshold)
+    }
+
+    /// Count an object
+    fn tally(&mut self, object: &Object, isolated: &World, seed: u64) {
+        let apgcode = apgcode::encode(isolated, object.class);
+        let existing = self.tallies[..self.length]
+            .iter_mut()
+            .flatten()
+            .find(|tally| match (&tally.apgcode, &apgcode) {
+                (Some(first), Some(second)) => first == second,
+                (None, None) => tally.hash == object.hash,
+                _ => false,
+            });
+        match existing {
+            Some(tally) => tally.count += 1,
+            None if self.length < Self::CAPACITY => {
+                self.tallies[self.length] = Some(Tally {
+                    name: object.name,
+                    class: object.class,
+                    hash: object.hash,
+                    population: object.population,
+                    count: 1,
+                    apgcode,
+                    seed,
+                });
+                self.length += 1;
+            }
+            None => {}
+        }
+    }
+}
+
+impl Default for Search {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn random_is_deterministic() {
+        let mut first = Random::new(42);
+        let mut second = Random::new(42);
+        for _ in 0..10 {
+            assert_eq!(first.next_u64(), second.next_u64());
+        }
+        assert_ne!(Random::new(1).next_u64(), Random::new(2).next_u64());
+    }
+
+    #[test]
+    fn soup_fills_the_middle() {
+        let world = soup(7);
+        let region = world.bounding_box().unwrap();
+
+        assert!(region.width <= SOUP_SIZE);
+        assert!(region.height <= SOUP_SIZE);
+        assert!(world.population() > SOUP_SIZE * SOUP_SIZE / 4);
+        assert_eq!(soup(7), world);
+    }
+
+    #[test]
+    fn stabilise_blinker() {
+        let mut world = World::new();
+        world.set_cell(8, 7, true);
+        world.set_cell(8, 8, true);
+        world.set_cell(8, 9, true);
+
+        assert_eq!(stabilise(&mut world, 10), Some(2));
+    }
+
+    #[test]
+    fn search_tallies_objects() {
+        let mut search = Search::new();
+        for seed in 0..4 {
+            search.run(seed);
+        }
+
+        let code = |tally: &Tally| tally.apgcode.unwrap().to_string();
+
+        let actual: Vec<_> = search
+            .tallies()
+            .map(|tally| (code(tally), tally.count, tally.seed))
+            .collect();
+        let expected = [
+            ("xs4_33", 5, 0),
+            ("xp2_7", 10, 0),
+            ("xs6_696", 2, 2),
+            ("xs7_2596", 1, 2),
+            ("xs4_252", 1, 2),
+            ("xs5_253", 1, 3),
+        ];
+        let expected: Vec<_> = expected
+            .iter()
+            .map(|(apgcode, count, seed)| (apgcode.to_string(), *count, *seed))
+            .collect();
+        assert_eq!(search.soups(), 4);
+        assert_eq!(search.unstable(), 0);
+        assert_eq!(actual, expected);
+
+        let rare: Vec<_> = search.rare(1).map(code).collect();
+        assert_eq!(rare, ["xs7_2596", "xs4_252", "xs5_253"]);
+        assert!(!rare.contains(&"xs4_33".to_string()));
+    }
+
+    #[test]
+    fn tally_same_object_once() {
+        let mut world = World::new();
+        world.set_cell(2, 2, true);
+        world.set_cell(3, 2, true);
+        world.set_cell(2, 3, true);
+        world.set_cell(3, 3, true);
+        world.set_cell(10, 10, true);
+        world.set_cell(11, 10, true);
+        world.set_cell(12, 10, true);
+
+        let mut search = Search::new();
+        Census::take_with(&world, |object, isolated| {
+            search.tally(object, isolated, 10)
+        });
+        Census::take_with(&world, |object, isolated| {
+            if object.class == Class::StillLife {
+                search.tally(object, isolated, 11);
+            }
+        });
+
+        let block = search.tallies().next().unwrap();
+        assert_eq!(block.count, 2);
+        assert_eq!(block.seed, 10);
+        assert_eq!(block.apgcode.unwrap().as_str(), "xs4_33");
+        assert_eq!(search.rare(1).count(), 1);
+    }
+
+    #[test]
+    fn tally_spaceship_in_any_direction() {
+        let mut search = Search::new();
+        let gliders = [
+            [(5, 5), (6, 6), (4, 7), (5, 7), (6, 7)],
+            [(5, 5), (4, 6), (4, 7), (5, 7), (6, 7)],
+        ];
+        for cells in gliders.iter() {
+            let mut world = World::new();
+            for (x, y) in cells.iter() {
+                world.set_cell(*x, *y, true);
+            }
+            Census::take_with(&world, |object, isolated| search.tally(object, isolated, 1));
+        }
+
+        let glider = search.tallies().next().unwrap();
+        assert_eq!(search.tallies().count(), 1);
+        assert_eq!(glider.count, 2);
+    }
+}
@@ -0,0 +1,298 @@
+// Copyright Claudio Mattera 2021.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Per-generation statistics
+
+use crate::game::{Pattern, World};
+
+const CELLS: usize = World::WIDTH * World::HEIGHT;
+
+/// Sizes of the square blocks over which spatial entropy is computed
+pub const BLOCK_SIZES: [usize; 3] = [1, 2, 3];
+
+/// Number of symbols counted in each pass over the world by [`block_entropy`]
+const SYMBOLS_PER_PASS: usize = 64;
+
+/// Symmetry measures of the live cells
+///
+/// Each measure is the fraction of live cells that are still alive after
+/// transforming the pattern within its bounding box, from 0 (no overlap) to 1
+/// (perfectly symmetric).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Symmetry {
+    /// Reflection around the vertical axis
+    pub horizontal: f32,
+
+    /// Reflection around the horizontal axis
+    pub vertical: f32,
+
+    /// Rotation by 180 degrees
+    pub rotational: f32,
+
+    /// Reflection around the main diagonal, only for square patterns
+    pub diagonal: f32,
+}
+
+impl Symmetry {
+    /// Measure the symmetry of a pattern
+    pub fn of(pattern: &Pattern) -> Self {
+        let overlap = |transformed: Pattern| {
+            let population = pattern.population();
+            if population == 0 {
+                return 1.0;
+            }
+            let common = pattern
+                .live_cells()
+                .filter(|(x, y)| transformed.get_cell(*x, *y))
+                .count();
+            common as f32 / population as f32
+        };
+
+        let diagonal = if pattern.width() == pattern.height() {
+            overlap(pattern.transformed(true, false, false))
+        } else {
+            0.0
+        };
+
+        Self {
+            horizontal: overlap(pattern.reflected_horizontally()),
+            vertical: overlap(pattern.reflected_vertically()),
+            rotational: overlap(pattern.transformed(false, true, true)),
+            diagonal,
+        }
+    }
+
+    /// Get the largest symmetry measure
+    pub fn max(&self) -> f32 {
+        self.horizontal
+            .max(self.vertical)
+            .max(self.rotational)
+            .max(self.diagonal)
+    }
+}
+
+/// Statistics of a generation
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Statistics {
+    pub population: usize,
+
+    /// Fraction of live cells
+    pub density: f32,
+
+    /// Number of cells, dead or alive, by their number of live neighbours
+    pub neighbours: [usize; 9],
+
+    /// Number of cells born since the previous generation
+    pub births: usize,
+
+    /// Number of cells dead since the previous generation
+    pub deaths: usize,
+
+    /// Spatial entropy in bits per cell, for each of the [`BLOCK_SIZES`]
+    pub entropy: [f32; BLOCK_SIZES.len()],
+
+    pub symmetry: Symmetry,
+}
+
+impl Statistics {
+    /// Compute the statistics of a generation
+    pub fn of(world: &World) -> Self {
+        let population = world.population();
+
+        let mut neighbours = [0; 9];
+        for y in 0..(World::HEIGHT as i16) {
+            for x in 0..(World::WIDTH as i16) {
+                neighbours[world.count_live_neighbours(x, y)] += 1;
+            }
+        }
+
+        let mut entropy = [0.0; BLOCK_SIZES.len()];
+        for (entropy, block_size) in entropy.iter_mut().zip(BLOCK_SIZES.iter()) {
+            *entropy = block_entropy(world, *block_size);
+        }
+
+        Self {
+            population,
+            density: population as f32 / CELLS as f32,
+            neighbours,
+            births: 0,
+            deaths: 0,
+            entropy,
+            symmetry: Symmetry::of(&world.pattern()),
+        }
+    }
+
+    /// Compute the statistics of a generation, including the changes since
+    /// the previous one
+    pub fn compute(previous: &World, current: &World) -> Self {
+        let mut statistics = Self::of(current);
+        for y in 0..(World::HEIGHT as i16) {
+            for x in 0..(World::WIDTH as i16) {
+                match (previous.get_cell(x, y), current.get_cell(x, y)) {
+                    (false, true) => statistics.births += 1,
+                    (true, false) => statistics.deaths += 1,
+                    _ => {}
+                }
+            }
+        }
+        statistics
+    }
+}
+
+/// Compute the spatial entropy of a world in bits per cell
+///
+/// Every square block of the given size, wrapping around the edges, is
+/// considered as a symbol, and the Shannon entropy of their distribution is
+/// divided by the number of cells in a block.
+/// Blocks larger than 3 are not supported, and have zero entropy.
+pub fn block_entropy(world: &World, block_size: usize) -> f32 {
+    if block_size == 0 || block_size > 3 {
+        return 0.0;
+    }
+
+    // Symbols are counted a few at a time to keep the table small
+    let symbols = 1 << (block_size * block_size);
+    let mut entropy = 0.0;
+    for first in (0..symbols).step_by(SYMBOLS_PER_PASS) {
+        let mut counts = [0u16; SYMBOLS_PER_PASS];
+        for y in 0..World::HEIGHT as i16 {
+            for x in 0..World::WIDTH as i16 {
+                let symbol = block_symbol(world, x, y, block_size);
+                if (first..first + SYMBOLS_PER_PASS).contains(&symbol) {
+                    counts[symbol - first] += 1;
+                }
+            }
+        }
+
+        for count in counts.iter().filter(|count| **count > 0) {
+            let probability = *count as f32 / CELLS as f32;
+            entropy -= probability * log2(probability);
+        }
+    }
+    entropy / (block_size * block_size) as f32
+}
+
+/// Read the block of cells with a corner at a position as a symbol
+fn block_symbol(world: &World, x: i16, y: i16, block_size: usize) -> usize {
+    let width = World::WIDTH as i16;
+    let height = World::HEIGHT as i16;
+    let mut symbol = 0;
+    for dy in 0..(block_size as i16) {
+        for dx in 0..(block_size as i16) {
+            let cell = world.get_cell((x + dx) % width, (y + dy) % height);
+            symbol = symbol << 1 | cell as usize;
+        }
+    }
+    symbol
+}
+
+/// Compute the binary logarithm of a positive number
+///
+/// Floating point functions from the standard library are not available, so
+/// the logarithm of the mantissa is approximated with a series.
+fn log2(x: f32) -> f32 {
+    let bits = x.to_bits();
+    let exponent = ((bits >> 23) & 0xff) as i32 - 127;
+    let mantissa = f32::from_bits((bits & 0x007f_ffff) | 0x3f80_0000);
+
+    let y = (mantissa - 1.0) / (mantissa + 1.0);
+    let y2 = y * y;
+    let ln = 2.0 * y * (1.0 + y2 * (1.0 / 3.0 + y2 * (1.0 / 5.0 + y2 * (1.0 / 7.0))));
+    exponent as f32 + ln * core::f32::consts::LOG2_E
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn checkerboard() -> World {
+        let mut world = World::new();
+        for y in 0..(World::HEIGHT as i16) {
+            for x in 0..(World::WIDTH as i16) {
+                world.set_cell(x, y, (x + y) % 2 == 0);
+            }
+        }
+        world
+    }
+
+    fn assert_close(actual: f32, expected: f32) {
+        assert!(
+            (actual - expected).abs() < 1e-4,
+            "{} is not close to {}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn logarithm() {
+        assert_close(log2(1.0), 0.0);
+        assert_close(log2(8.0), 3.0);
+        assert_close(log2(0.25), -2.0);
+        assert_close(log2(3.0), 1.5849625);
+        assert_close(log2(0.7), -0.5145732);
+    }
+
+    #[test]
+    fn empty_world() {
+        let statistics = Statistics::of(&World::new());
+
+        assert_eq!(statistics.population, 0);
+        assert_eq!(statistics.neighbours[0], CELLS);
+        assert_eq!(statistics.entropy, [0.0; 3]);
+        assert_eq!(statistics.symmetry.max(), 1.0);
+    }
+
+    #[test]
+    fn checkerboard_entropy() {
+        let world = checkerboard();
+
+        let statistics = Statistics::of(&world);
+
+        assert_close(statistics.density, 0.5);
+        assert_eq!(statistics.neighbours[4], CELLS);
+        assert_close(statistics.entropy[0], 1.0);
+        assert_close(statistics.entropy[1], 0.25);
+        assert_close(statistics.entropy[2], 1.0 / 9.0);
+    }
+
+    #[test]
+    fn blinker_births_and_deaths() {
+        let mut world = World::new();
+        world.set_cell(8, 7, true);
+        world.set_cell(8, 8, true);
+        world.set_cell(8, 9, true);
+        let previous = world.clone();
+        world.step();
+
+        let statistics = Statistics::compute(&previous, &world);
+
+        assert_eq!(statistics.population, 3);
+        assert_eq!(statistics.births, 2);
+        assert_eq!(statistics.deaths, 2);
+        assert_eq!(statistics.neighbours.iter().sum::<usize>(), CELLS);
+        assert_eq!(statistics.neighbours[3], 2);
+        assert_eq!(statistics.symmetry.horizontal, 1.0);
+        assert_eq!(statistics.symmetry.rotational, 1.0);
+        assert_eq!(statistics.symmetry.diagonal, 0.0);
+    }
+
+    #[test]
+    fn glider_symmetry() {
+        let mut world = World::new();
+        world.set_cell(8, 6, true);
+        world.set_cell(9, 7, true);
+        world.set_cell(7, 8, true);
+        world.set_cell(8, 8, true);
+        world.set_cell(9, 8, true);
+
+        let symmetry = Statistics::of(&world).symmetry;
+
+        assert_close(symmetry.horizontal, 0.8);
+        assert_close(symmetry.vertical, 0.6);
+        assert_close(symmetry.diagonal, 0.6);
+    }
+}
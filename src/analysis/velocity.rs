@@ -0,0 +1,116 @@
+// Copyright Claudio Mattera 2021.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Velocity of moving patterns
+
+/// Direction of a moving pattern
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Direction {
+    /// Moving along rows or columns
+    Orthogonal,
+
+    /// Moving along diagonals
+    Diagonal,
+
+    /// Moving along any other direction
+    Oblique,
+}
+
+impl Direction {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Direction::Orthogonal => "orthogonal",
+            Direction::Diagonal => "diagonal",
+            Direction::Oblique => "oblique",
+        }
+    }
+}
+
+/// Velocity of a pattern that repeats itself after moving
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Velocity {
+    pub period: usize,
+    pub dx: i16,
+    pub dy: i16,
+}
+
+impl Velocity {
+    pub const fn new(period: usize, dx: i16, dy: i16) -> Self {
+        Self { period, dx, dy }
+    }
+
+    pub fn direction(&self) -> Direction {
+        if self.dx == 0 || self.dy == 0 {
+            Direction::Orthogonal
+        } else if self.dx.abs() == self.dy.abs() {
+            Direction::Diagonal
+        } else {
+            Direction::Oblique
+        }
+    }
+
+    /// Get the speed as a reduced fraction of the speed of light
+    ///
+    /// The speed of light is one cell per generation, so a glider moving by
+    /// one cell diagonally every four generations has speed `(1, 4)`, i.e.
+    /// _c/4_.
+    pub fn speed(&self) -> (usize, usize) {
+        let distance = self.dx.unsigned_abs().max(self.dy.unsigned_abs()) as usize;
+        let divisor = gcd(distance, self.period).max(1);
+        (distance / divisor, self.period / divisor)
+    }
+}
+
+/// Wrap a displacement on a toroidal axis to the range `(-size / 2, size / 2]`
+pub(super) fn wrap(delta: i16, size: i16) -> i16 {
+    let delta = delta.rem_euclid(size);
+    if delta > size / 2 {
+        delta - size
+    } else {
+        delta
+    }
+}
+
+fn gcd(a: usize, b: usize) -> usize {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn glider() {
+        let velocity = Velocity::new(4, 1, -1);
+        assert_eq!(velocity.direction(), Direction::Diagonal);
+        assert_eq!(velocity.speed(), (1, 4));
+    }
+
+    #[test]
+    fn lightweight_spaceship() {
+        let velocity = Velocity::new(4, -2, 0);
+        assert_eq!(velocity.direction(), Direction::Orthogonal);
+        assert_eq!(velocity.speed(), (1, 2));
+    }
+
+    #[test]
+    fn knightship() {
+        let velocity = Velocity::new(6, 2, 1);
+        assert_eq!(velocity.direction(), Direction::Oblique);
+        assert_eq!(velocity.speed(), (1, 3));
+    }
+
+    #[test]
+    fn wrap_displacement() {
+        assert_eq!(wrap(37, 38), -1);
+        assert_eq!(wrap(-1, 38), -1);
+        assert_eq!(wrap(2, 38), 2);
+    }
+}
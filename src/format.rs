@@ -0,0 +1,54 @@
+// Copyright Claudio Mattera 2021.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Pattern file formats
+
+use crate::game::World;
+
+mod error;
+pub use error::{Error, ErrorKind};
+
+mod metadata;
+pub use metadata::Metadata;
+
+pub mod apgcode;
+pub mod cells;
+pub mod embed;
+pub mod life105;
+pub mod life106;
+#[cfg(any(feature = "std", test))]
+pub mod macrocell;
+pub mod rle;
+
+/// Default origin for formats with coordinates, the centre of the world
+pub const DEFAULT_ORIGIN: (i16, i16) = (World::WIDTH as i16 / 2, World::HEIGHT as i16 / 2);
+
+/// Map coordinates relative to an origin onto the world
+fn wrapped(x: i64, y: i64, origin: (i16, i16)) -> (i16, i16) {
+    (
+        (origin.0 as i64 + x).rem_euclid(World::WIDTH as i64) as i16,
+        (origin.1 as i64 + y).rem_euclid(World::HEIGHT as i64) as i16,
+    )
+}
+
+/// Map a cell of the world to coordinates relative to an origin
+///
+/// Coordinates are in the range from minus half to plus half the size of the
+/// world.
+fn relative(x: i16, y: i16, origin: (i16, i16)) -> (i16, i16) {
+    let wrap = |delta: i16, size: i16| {
+        let delta = delta.rem_euclid(size);
+        if delta > size / 2 {
+            delta - size
+        } else {
+            delta
+        }
+    };
+    (
+        wrap(x - origin.0, World::WIDTH as i16),
+        wrap(y - origin.1, World::HEIGHT as i16),
+    )
+}
@@ -0,0 +1,440 @@
+// Copyright Claudio Mattera 2021.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Apgcodes, the identifiers of objects on Catagolue
+//!
+//! An apgcode is a prefix with the class of an object, followed by its cells
+//! in extended Wechsler format, e.g. `xs4_33` for a block, `xp2_7` for a
+//! blinker and `xq4_153` for a glider.
+//!
+//! Cells are split in strips of five rows, separated by `z`.
+//! Each column of a strip is a character from `0` to `v`, whose bits are the
+//! cells from top to bottom, and trailing empty columns are dropped.
+//! Runs of empty columns are shortened to `w` (two), `x` (three) or `y`
+//! followed by a character from `0` to `z` (four to thirty-nine).
+//!
+//! Among all phases and orientations of an object, its apgcode is the
+//! shortest one, and the first in alphabetical order among those.
+
+use core::fmt;
+use core::str::from_utf8_unchecked;
+
+use crate::analysis::Class;
+use crate::game::{Pattern, World};
+
+use super::{Error, ErrorKind};
+
+/// Characters of columns, and of runs of empty columns after `y`
+const DIGITS: &[u8; 36] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+
+/// Number of rows of a strip
+const STRIP_HEIGHT: usize = 5;
+
+/// An apgcode
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct Apgcode {
+    bytes: [u8; Apgcode::CAPACITY],
+    length: usize,
+}
+
+impl Apgcode {
+    /// Maximal length of an apgcode, enough for a pattern as large as the
+    /// world
+    pub const CAPACITY: usize = 168;
+
+    const fn new() -> Self {
+        Self {
+            bytes: [0; Self::CAPACITY],
+            length: 0,
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        unsafe { from_utf8_unchecked(&self.bytes[..self.length]) }
+    }
+
+    /// Check whether this apgcode is shorter than another, or as long and
+    /// first in alphabetical order
+    fn precedes(&self, other: &Self) -> bool {
+        (self.length, self.as_str()) < (other.length, other.as_str())
+    }
+
+    fn push(&mut self, byte: u8) {
+        if self.length < Self::CAPACITY {
+            self.bytes[self.length] = byte;
+            self.length += 1;
+        }
+    }
+
+    fn push_number(&mut self, value: usize) {
+        if value >= 10 {
+            self.push_number(value / 10);
+        }
+        self.push(b'0' + (value % 10) as u8);
+    }
+
+    /// Append the cells of a pattern in extended Wechsler format
+    fn push_cells(&mut self, pattern: &Pattern) {
+        for top in (0..pattern.height()).step_by(STRIP_HEIGHT) {
+            if top > 0 {
+                self.push(b'z');
+            }
+            let mut empty = 0;
+            for x in 0..pattern.width() {
+                let mut column = 0;
+                for row in 0..STRIP_HEIGHT {
+                    let y = top + row;
+                    if y < pattern.height() && pattern.get_cell(x, y) {
+                        column |= 1 << row;
+                    }
+                }
+                if column == 0 {
+                    empty += 1;
+                } else {
+                    self.push_empty_columns(empty);
+                    self.push(DIGITS[column]);
+                    empty = 0;
+                }
+            }
+        }
+    }
+
+    fn push_empty_columns(&mut self, mut count: usize) {
+        while count > 0 {
+            match count {
+                1 => self.push(b'0'),
+                2 => self.push(b'w'),
+                3 => self.push(b'x'),
+                _ => {
+                    let run = count.min(4 + DIGITS.len() - 1);
+                    self.push(b'y');
+                    self.push(DIGITS[run - 4]);
+                    count -= run;
+                    continue;
+                }
+            }
+            count = 0;
+        }
+    }
+}
+
+impl fmt::Display for Apgcode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl fmt::Debug for Apgcode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+/// Prefix of an apgcode
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Prefix {
+    /// A still life with the given population, `xs`
+    StillLife { population: usize },
+
+    /// An oscillator with the given period, `xp`
+    Oscillator { period: usize },
+
+    /// A spaceship with the given period, `xq`
+    Spaceship { period: usize },
+}
+
+/// Compute the apgcode of an object of a given class
+///
+/// The world must contain the object alone, as a census isolates it.
+/// Empty worlds and objects of unknown class have no apgcode.
+pub fn encode(world: &World, class: Class) -> Option<Apgcode> {
+    let (prefix, number, period) = match class {
+        Class::StillLife => (b's', world.population(), 1),
+        Class::Oscillator { period } => (b'p', period, period),
+        Class::Spaceship(velocity) => (b'q', velocity.period, velocity.period),
+        Class::Unknown => return None,
+    };
+    if world.population() == 0 {
+        return None;
+    }
+
+    let mut best: Option<Apgcode> = None;
+    let mut world = world.clone();
+    for _ in 0..period {
+        let pattern = world.pattern();
+        for symmetry in 0..8 {
+            let pattern =
+                pattern.transformed(symmetry & 4 != 0, symmetry & 2 != 0, symmetry & 1 != 0);
+            let mut code = Apgcode::new();
+            code.push(b'x');
+            code.push(prefix);
+            code.push_number(number);
+            code.push(b'_');
+            code.push_cells(&pattern);
+            let better = match best {
+                Some(best) => code.precedes(&best),
+                None => true,
+            };
+            if better {
+                best = Some(code);
+            }
+        }
+        world.step();
+    }
+    best
+}
+
+/// Parse an apgcode
+///
+/// Patterns may be larger than the world in the orientation of the apgcode,
+/// as long as they fit once rotated.
+/// The cells are not checked against the prefix, e.g. `xs5_33` is parsed
+/// as a block.
+pub fn decode(code: &str) -> Result<(Prefix, Pattern), Error> {
+    let separator = code
+        .find('_')
+        .ok_or_else(|| Error::new(ErrorKind::InvalidHeader, 1, 1))?;
+    let (head, cells) = (&code[..separator], &code[separator + 1..]);
+
+    let kind = head.get(..2);
+    if !matches!(kind, Some("xs") | Some("xp") | Some("xq")) {
+        return Err(Error::new(ErrorKind::InvalidHeader, 1, 1));
+    }
+    let number = head[2..]
+        .parse::<usize>()
+        .ok()
+        .filter(|number| *number > 0)
+        .ok_or_else(|| Error::new(ErrorKind::InvalidNumber, 1, 3))?;
+    let prefix = match kind {
+        Some("xs") => Prefix::StillLife { population: number },
+        Some("xp") => Prefix::Oscillator { period: number },
+        _ => Prefix::Spaceship { period: number },
+    };
+
+    let column = separator + 2;
+    let mut width = 0;
+    let mut height = 0;
+    visit_cells(cells, column, |x, y| {
+        width = width.max(x + 1);
+        height = height.max(y + 1);
+    })?;
+    let fits = |width, height| width <= World::WIDTH && height <= World::HEIGHT;
+    if !fits(width, height) && !fits(height, width) {
+        return Err(Error::new(ErrorKind::TooLarge, 1, column));
+    }
+
+    let mut pattern = Pattern::new(width, height);
+    visit_cells(cells, column, |x, y| pattern.set_cell(x, y, true))?;
+    Ok((prefix, pattern))
+}
+
+/// Call a function on each live cell of cells in extended Wechsler format
+///
+/// The column of the first character is used to locate errors.
+fn visit_cells<F>(cells: &str, column: usize, mut visit: F) -> Result<(), Error>
+where
+    F: FnMut(usize, usize),
+{
+    let mut x = 0;
+    let mut y = 0;
+    let mut characters = cells.char_indices();
+    while let Some((i, character)) = characters.next() {
+        match character {
+            '0'..='9' | 'a'..='v' => {
+                let bits = character.to_digit(32).unwrap_or(0);
+                for row in 0..STRIP_HEIGHT {
+                    if bits & 1 << row != 0 {
+                        visit(x, y + row);
+                    }
+                }
+                x += 1;
+            }
+            'w' => x += 2,
+            'x' => x += 3,
+            'y' => match characters.next() {
+                Some((_, run @ '0'..='9')) | Some((_, run @ 'a'..='z')) => {
+                    x += 4 + run.to_digit(36).unwrap_or(0) as usize;
+                }
+                Some((i, _)) => return Err(Error::new(ErrorKind::InvalidNumber, 1, column + i)),
+                None => {
+                    let kind = ErrorKind::InvalidNumber;
+                    return Err(Error::new(kind, 1, column + cells.len()));
+                }
+            },
+            'z' => {
+                x = 0;
+                y += STRIP_HEIGHT;
+            }
+            _ => {
+                let kind = ErrorKind::UnexpectedCharacter(character);
+                return Err(Error::new(kind, 1, column + i));
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::analysis::{Census, Velocity};
+    use crate::format::embed;
+
+    fn apgcode(pattern: &Pattern, class: Class) -> String {
+        let world = World::from_pattern(pattern);
+        encode(&world, class).unwrap().to_string()
+    }
+
+    #[test]
+    fn still_lifes() {
+        let cases = [
+            ("2o$2o!", "xs4_33"),
+            ("b2o$o2bo$b2o!", "xs6_696"),
+            ("b2o$o2bo$bobo$2bo!", "xs7_2596"),
+            ("b2o$o2bo$o2bo$b2o!", "xs8_6996"),
+        ];
+        for (rle, expected) in cases.iter() {
+            let actual = apgcode(&embed::rle(rle), Class::StillLife);
+
+            assert_eq!(&actual, expected);
+        }
+    }
+
+    #[test]
+    fn oscillators() {
+        let blinker = embed::rle("3o!");
+        let pentadecathlon = embed::rle("2bo4bo$2ob4ob2o$2bo4bo!");
+        let pulsar = embed::rle(
+            "2b3o3b3o2b2$o4bobo4bo$o4bobo4bo$o4bobo4bo$2b3o3b3o2b2$\
+             2b3o3b3o2b$o4bobo4bo$o4bobo4bo$o4bobo4bo2$2b3o3b3o!",
+        );
+
+        let actual = apgcode(&blinker, Class::Oscillator { period: 2 });
+        assert_eq!(actual, "xp2_7");
+
+        let actual = apgcode(&pentadecathlon, Class::Oscillator { period: 15 });
+        assert_eq!(actual, "xp15_4r4z4r4");
+
+        let actual = apgcode(&pulsar, Class::Oscillator { period: 3 });
+        assert_eq!(actual, "xp3_co9nas0san9oczgoldlo0oldlogz1047210127401");
+    }
+
+    #[test]
+    fn spaceships() {
+        let cases = [
+            ("bo$2bo$3o!", 1, "xq4_153"),
+            ("bo2bo$o4b$o3bo$4o!", 0, "xq4_6frc"),
+            ("3bo2b$bo3bo$o5b$o4bo$5o!", 0, "xq4_27dee6"),
+        ];
+        for (rle, dy, expected) in cases.iter() {
+            let class = Class::Spaceship(Velocity::new(4, 2 - *dy, *dy));
+            let actual = apgcode(&embed::rle(rle), class);
+
+            assert_eq!(&actual, expected);
+        }
+    }
+
+    #[test]
+    fn census_objects() {
+        let mut world = World::new();
+        world.place_pattern(&embed::rle("2o$2o!"), 2, 2);
+        world.place_pattern(&embed::rle("3o!"), 20, 10);
+
+        let mut codes = Vec::new();
+        Census::take_with(&world, |object, isolated| {
+            codes.push(encode(isolated, object.class).unwrap().to_string());
+        });
+
+        assert_eq!(codes, ["xs4_33", "xp2_7"]);
+    }
+
+    #[test]
+    fn census_pseudo_objects() {
+        let mut world = World::new();
+        world.place_pattern(&embed::rle("3o2$4bo$4bo$4bo!"), 10, 6);
+
+        let mut codes = Vec::new();
+        Census::take_with(&world, |object, isolated| {
+            codes.push(encode(isolated, object.class).unwrap().to_string());
+        });
+
+        assert_eq!(codes, ["xp2_7", "xp2_7"]);
+    }
+
+    #[test]
+    fn unknown_and_empty() {
+        let world = World::from_pattern(&embed::rle("3o!"));
+
+        assert_eq!(encode(&world, Class::Unknown), None);
+        assert_eq!(encode(&World::new(), Class::StillLife), None);
+    }
+
+    #[test]
+    fn decode_codes() {
+        let (prefix, pattern) = decode("xq4_153").unwrap();
+        assert_eq!(prefix, Prefix::Spaceship { period: 4 });
+        assert_eq!(
+            pattern,
+            embed::rle("o$obo$2o!").transformed(true, false, false)
+        );
+
+        let (prefix, pattern) = decode("xp15_4r4z4r4").unwrap();
+        assert_eq!(prefix, Prefix::Oscillator { period: 15 });
+        assert_eq!((pattern.width(), pattern.height()), (3, 10));
+        assert_eq!(pattern.population(), 12);
+
+        let (_, pattern) = decode("xs2_1y01").unwrap();
+        assert_eq!((pattern.width(), pattern.height()), (6, 1));
+
+        let (_, pattern) = decode("xs2_1z0z0z0z0z0z1").unwrap();
+        assert_eq!((pattern.width(), pattern.height()), (1, 31));
+    }
+
+    #[test]
+    fn round_trip() {
+        let codes = [
+            "xs4_33",
+            "xs6_696",
+            "xp2_7",
+            "xq4_153",
+            "xq4_27dee6",
+            "xp3_co9nas0san9oczgoldlo0oldlogz1047210127401",
+        ];
+        for code in codes.iter() {
+            let (prefix, pattern) = decode(code).unwrap();
+            let class = match prefix {
+                Prefix::StillLife { .. } => Class::StillLife,
+                Prefix::Oscillator { period } => Class::Oscillator { period },
+                Prefix::Spaceship { period } => Class::Spaceship(Velocity::new(period, 1, 1)),
+            };
+
+            let actual = apgcode(&pattern, class);
+
+            assert_eq!(&actual, code);
+        }
+    }
+
+    #[test]
+    fn invalid_codes() {
+        let cases = [
+            ("xs4", ErrorKind::InvalidHeader, 1),
+            ("xr4_33", ErrorKind::InvalidHeader, 1),
+            ("xs_33", ErrorKind::InvalidNumber, 3),
+            ("xp0_7", ErrorKind::InvalidNumber, 3),
+            ("xs4_3A", ErrorKind::UnexpectedCharacter('A'), 6),
+            ("xs2_1y", ErrorKind::InvalidNumber, 7),
+            ("xs2_1yz1", ErrorKind::TooLarge, 5),
+            ("xs9_1z1z1z1z1z1z1z1z1", ErrorKind::TooLarge, 5),
+        ];
+        for (code, kind, column) in cases.iter() {
+            let actual = decode(code).unwrap_err();
+
+            let expected = Error::new(*kind, 1, *column);
+            assert_eq!(actual, expected);
+        }
+    }
+}
@@ -0,0 +1,217 @@
+// Copyright Claudio Mattera 2021.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Plaintext format
+//!
+//! This is the `.cells` format used by LifeWiki, e.g. a glider is written as
+//! follows.
+//!
+//! ~~~~text
+//! !Name: Glider
+//! !The smallest spaceship
+//! .O.
+//! ..O
+//! OOO
+//! ~~~~
+
+use core::fmt;
+
+use crate::game::{Pattern, World};
+
+use super::{Error, ErrorKind, Metadata};
+
+/// A pattern read from a plaintext file
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Cells<'a> {
+    pub metadata: Metadata<'a>,
+    pub pattern: Pattern,
+}
+
+impl Cells<'_> {
+    /// Create a world with the pattern in its centre
+    pub fn world(&self) -> World {
+        World::from_pattern(&self.pattern)
+    }
+}
+
+/// Parse a pattern in plaintext format
+///
+/// Lines starting with `!Name:` and `!Author:` are read as name and author,
+/// other lines starting with `!` are read as comments.
+/// Rows can be shorter than the pattern, and empty lines are empty rows,
+/// except at the end of the file.
+pub fn parse(input: &str) -> Result<Cells<'_>, Error> {
+    let mut metadata = Metadata::new();
+    let mut width = 0;
+    let mut height = 0;
+    let mut rows = 0;
+
+    for (line, number) in input.lines().zip(1..) {
+        if let Some(comment) = line.strip_prefix('!') {
+            read_metadata(comment.trim(), &mut metadata);
+            continue;
+        }
+
+        let row = line.trim_end();
+        for (offset, character) in row.char_indices() {
+            if character != '.' && character != 'O' {
+                let kind = ErrorKind::UnexpectedCharacter(character);
+                return Err(Error::new(kind, number, offset + 1));
+            }
+        }
+
+        rows += 1;
+        if !row.is_empty() {
+            width = width.max(row.len());
+            height = rows;
+        }
+        if width > World::WIDTH || height > World::HEIGHT {
+            let column = if width > World::WIDTH {
+                World::WIDTH + 1
+            } else {
+                1
+            };
+            return Err(Error::new(ErrorKind::TooLarge, number, column));
+        }
+    }
+
+    let mut pattern = Pattern::new(width, height);
+    let rows = input.lines().filter(|line| !line.starts_with('!'));
+    for (y, row) in rows.take(height).enumerate() {
+        for (x, character) in row.trim_end().bytes().enumerate() {
+            pattern.set_cell(x, y, character == b'O');
+        }
+    }
+
+    Ok(Cells { metadata, pattern })
+}
+
+/// Write a pattern in plaintext format
+///
+/// Every row is written with the full width of the pattern.
+pub fn write<W>(out: &mut W, pattern: &Pattern, metadata: &Metadata<'_>) -> fmt::Result
+where
+    W: fmt::Write,
+{
+    if let Some(name) = metadata.name {
+        writeln!(out, "!Name: {}", name)?;
+    }
+    if let Some(author) = metadata.author {
+        writeln!(out, "!Author: {}", author)?;
+    }
+    for comment in metadata.comments() {
+        writeln!(out, "!{}", comment)?;
+    }
+
+    for y in 0..pattern.height() {
+        for x in 0..pattern.width() {
+            out.write_char(if pattern.get_cell(x, y) { 'O' } else { '.' })?;
+        }
+        writeln!(out)?;
+    }
+    Ok(())
+}
+
+/// Write the live cells of a world in plaintext format
+pub fn write_world<W>(out: &mut W, world: &World, metadata: &Metadata<'_>) -> fmt::Result
+where
+    W: fmt::Write,
+{
+    write(out, &world.pattern(), metadata)
+}
+
+fn read_metadata<'a>(comment: &'a str, metadata: &mut Metadata<'a>) {
+    if let Some(name) = comment.strip_prefix("Name:") {
+        metadata.name = Some(name.trim());
+    } else if let Some(author) = comment.strip_prefix("Author:") {
+        metadata.author = Some(author.trim());
+    } else {
+        metadata.push_comment(comment);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GLIDER: &str = "!Name: Glider
+!Author: Richard K. Guy
+!The smallest, most common, and first discovered spaceship.
+.O
+..O
+OOO
+";
+
+    #[test]
+    fn parse_glider() {
+        let cells = parse(GLIDER).unwrap();
+
+        assert_eq!(cells.metadata.name, Some("Glider"));
+        assert_eq!(cells.metadata.author, Some("Richard K. Guy"));
+        assert_eq!(
+            cells.metadata.comments().next(),
+            Some("The smallest, most common, and first discovered spaceship.")
+        );
+        assert_eq!((cells.pattern.width(), cells.pattern.height()), (3, 3));
+
+        let cells: Vec<(usize, usize)> = cells.pattern.live_cells().collect();
+        assert_eq!(cells, vec![(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)]);
+    }
+
+    #[test]
+    fn empty_rows() {
+        let cells = parse("O\n\n.O\n\n\n").unwrap();
+
+        assert_eq!((cells.pattern.width(), cells.pattern.height()), (2, 3));
+        assert!(cells.pattern.get_cell(0, 0));
+        assert!(cells.pattern.get_cell(1, 2));
+    }
+
+    #[test]
+    fn unexpected_character() {
+        let actual = parse("!Name: Test\n.O.\n.*.\n");
+
+        assert_eq!(
+            actual,
+            Err(Error::new(ErrorKind::UnexpectedCharacter('*'), 3, 2))
+        );
+    }
+
+    #[test]
+    fn pattern_too_large() {
+        let row = ".".repeat(World::WIDTH) + "O";
+
+        let actual = parse(&row);
+
+        assert_eq!(
+            actual,
+            Err(Error::new(ErrorKind::TooLarge, 1, World::WIDTH + 1))
+        );
+    }
+
+    #[test]
+    fn write_glider() {
+        let cells = parse(GLIDER).unwrap();
+        let mut metadata = Metadata::new();
+        metadata.name = Some("Glider");
+
+        let mut actual = String::new();
+        write(&mut actual, &cells.pattern, &metadata).unwrap();
+
+        assert_eq!(actual, "!Name: Glider\n.O.\n..O\nOOO\n");
+    }
+
+    #[test]
+    fn round_trip() {
+        let cells = parse(GLIDER).unwrap();
+
+        let mut output = String::new();
+        write_world(&mut output, &cells.world(), &cells.metadata).unwrap();
+        let actual = parse(&output).unwrap();
+
+        assert_eq!(actual, cells);
+    }
+}
@@ -0,0 +1,221 @@
+// Copyright Claudio Mattera 2021.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Compile-time embedding of patterns
+//!
+//! Patterns written in RLE or plaintext format are parsed by `const fn`s, so
+//! they can be stored in constants and cost nothing at runtime.
+//!
+//! ~~~~rust
+//! use game_of_life::format::embed;
+//! use game_of_life::game::Pattern;
+//!
+//! const GLIDER: Pattern = embed::rle("bo$2bo$3o!");
+//! const BLINKER: Pattern = embed::plaintext("OOO");
+//! ~~~~
+//!
+//! Only cells are read: RLE headers, comments and metadata are skipped, and
+//! the size of the pattern is the size of its cells.
+//! Constant evaluation cannot panic, so invalid input fails it by indexing
+//! out of bounds, which stops compilation when parsing a constant.
+
+use crate::game::{Pattern, World};
+
+/// Parse the cells of a pattern in RLE format
+///
+/// Lines starting with `#` or `x` are skipped, and everything after the
+/// first `!` is ignored.
+pub const fn rle(input: &str) -> Pattern {
+    let (_, width, height) = rle_cells(input, Pattern::new(0, 0));
+    if width > World::WIDTH || height > World::HEIGHT {
+        invalid(input.len());
+    }
+    let (pattern, _, _) = rle_cells(input, Pattern::new(width, height));
+    pattern
+}
+
+/// Parse the cells of a pattern in plaintext format
+///
+/// Lines starting with `!` are skipped, and empty lines are empty rows,
+/// except at the end.
+pub const fn plaintext(input: &str) -> Pattern {
+    let (_, width, height) = plaintext_cells(input, Pattern::new(0, 0));
+    if width > World::WIDTH || height > World::HEIGHT {
+        invalid(input.len());
+    }
+    let (pattern, _, _) = plaintext_cells(input, Pattern::new(width, height));
+    pattern
+}
+
+/// Make the cells of a RLE pattern alive, returning the pattern and the size
+/// of the cells
+const fn rle_cells(input: &str, mut pattern: Pattern) -> (Pattern, usize, usize) {
+    let bytes = input.as_bytes();
+    let mut width = 0;
+    let mut height = 0;
+    let mut x = 0;
+    let mut y = 0;
+    let mut run = 0;
+    let mut line_start = true;
+    let mut i = 0;
+    while i < bytes.len() {
+        let byte = bytes[i];
+        if line_start && (byte == b'#' || byte == b'x') {
+            while i < bytes.len() && bytes[i] != b'\n' {
+                i += 1;
+            }
+            continue;
+        }
+        line_start = byte == b'\n';
+
+        let count = if run == 0 { 1 } else { run };
+        match byte {
+            b'0'..=b'9' => {
+                run = run * 10 + (byte - b'0') as usize;
+                i += 1;
+                continue;
+            }
+            b'b' | b'.' | b'o' => {
+                if byte == b'o' {
+                    let mut dx = 0;
+                    while dx < count {
+                        pattern = pattern.with_cell(x + dx, y);
+                        dx += 1;
+                    }
+                }
+                x += count;
+                if x > width {
+                    width = x;
+                }
+                height = y + 1;
+            }
+            b'$' => {
+                x = 0;
+                y += count;
+            }
+            b'!' => break,
+            b' ' | b'\t' | b'\r' | b'\n' => {}
+            _ => {
+                invalid(i);
+            }
+        }
+        run = 0;
+        i += 1;
+    }
+    (pattern, width, height)
+}
+
+/// Make the cells of a plaintext pattern alive, returning the pattern and the
+/// size of the cells
+const fn plaintext_cells(input: &str, mut pattern: Pattern) -> (Pattern, usize, usize) {
+    let bytes = input.as_bytes();
+    let mut width = 0;
+    let mut height = 0;
+    let mut x = 0;
+    let mut y = 0;
+    let mut line_start = true;
+    let mut i = 0;
+    while i < bytes.len() {
+        let byte = bytes[i];
+        if line_start && byte == b'!' {
+            while i < bytes.len() && bytes[i] != b'\n' {
+                i += 1;
+            }
+            i += 1;
+            continue;
+        }
+        line_start = byte == b'\n';
+
+        match byte {
+            b'.' | b'O' => {
+                if byte == b'O' {
+                    pattern = pattern.with_cell(x, y);
+                }
+                x += 1;
+                if x > width {
+                    width = x;
+                }
+                height = y + 1;
+            }
+            b'\n' => {
+                x = 0;
+                y += 1;
+            }
+            b' ' | b'\t' | b'\r' => {}
+            _ => {
+                invalid(i);
+            }
+        }
+        i += 1;
+    }
+    (pattern, width, height)
+}
+
+/// Fail the evaluation by indexing an empty array
+///
+/// The index is the position of the invalid byte in the input, or the length
+/// of the input for patterns larger than the world, to make it visible in the
+/// error.
+const fn invalid(position: usize) -> usize {
+    let positions: [usize; 0] = [];
+    positions[position]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::format::{cells, rle as runtime_rle};
+
+    const GLIDER: Pattern = rle("#N Glider\nx = 3, y = 3, rule = B3/S23\nbo$2bo$3o!");
+
+    #[test]
+    fn rle_glider() {
+        let expected = runtime_rle::parse("x = 3, y = 3\nbo$2bo$3o!").unwrap();
+
+        assert_eq!(GLIDER, expected.pattern);
+    }
+
+    #[test]
+    fn rle_runs_across_lines() {
+        let actual = rle("x = 12, y = 3\n2o10b$\n12o$\n3bo!trailing");
+
+        let expected = runtime_rle::parse("x = 12, y = 3\n2o10b$12o$3bo!").unwrap();
+        assert_eq!(actual, expected.pattern);
+    }
+
+    #[test]
+    fn rle_skipped_rows() {
+        let actual = rle("o2$o!");
+
+        assert_eq!((actual.width(), actual.height()), (1, 3));
+        assert_eq!(actual.population(), 2);
+        assert!(actual.get_cell(0, 2));
+    }
+
+    #[test]
+    fn plaintext_pattern() {
+        let input = "!Name: Beehive\n.OO.\nO..O\n\n.OO\n";
+
+        let actual = plaintext(input);
+
+        let expected = cells::parse(input).unwrap();
+        assert_eq!(actual, expected.pattern);
+        assert_eq!((actual.width(), actual.height()), (4, 4));
+    }
+
+    #[test]
+    #[should_panic]
+    fn invalid_character() {
+        rle("bo$2bq!");
+    }
+
+    #[test]
+    #[should_panic]
+    fn too_large() {
+        plaintext("O.......................................O");
+    }
+}
@@ -0,0 +1,83 @@
+// Copyright Claudio Mattera 2021.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Errors in pattern files
+
+use core::fmt;
+
+/// Kind of error in a pattern file
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// The header line with the size of the pattern is missing
+    MissingHeader,
+
+    /// The header line is malformed
+    InvalidHeader,
+
+    /// The rule is not a Life-like rule
+    InvalidRule,
+
+    /// A character is not allowed at this position
+    UnexpectedCharacter(char),
+
+    /// A number is malformed or out of range
+    InvalidNumber,
+
+    /// A cell lies outside the declared size of the pattern
+    OutOfBounds,
+
+    /// The pattern is larger than the world
+    TooLarge,
+
+    /// A node refers to a node that is not defined, or has the wrong size
+    InvalidReference,
+}
+
+/// An error in a pattern file
+///
+/// Lines and columns are counted from one.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Error {
+    pub kind: ErrorKind,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Error {
+    pub const fn new(kind: ErrorKind, line: usize, column: usize) -> Self {
+        Self { kind, line, column }
+    }
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ErrorKind::MissingHeader => f.write_str("missing header"),
+            ErrorKind::InvalidHeader => f.write_str("invalid header"),
+            ErrorKind::InvalidRule => f.write_str("invalid rule"),
+            ErrorKind::UnexpectedCharacter(character) => {
+                write!(f, "unexpected character {:?}", character)
+            }
+            ErrorKind::InvalidNumber => f.write_str("invalid number"),
+            ErrorKind::OutOfBounds => f.write_str("cell out of bounds"),
+            ErrorKind::TooLarge => f.write_str("pattern larger than the world"),
+            ErrorKind::InvalidReference => f.write_str("invalid node reference"),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} at line {}, column {}",
+            self.kind, self.line, self.column
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
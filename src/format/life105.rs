@@ -0,0 +1,277 @@
+// Copyright Claudio Mattera 2021.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Life 1.05 format
+//!
+//! This format is a list of blocks of cells, each preceded by the position of
+//! its top-left corner, e.g. a glider is written as follows.
+//!
+//! ~~~~text
+//! #Life 1.05
+//! #D The smallest spaceship
+//! #N
+//! #P -1 -1
+//! .*.
+//! ..*
+//! ***
+//! ~~~~
+
+use core::fmt;
+
+use crate::game::{Rule, World};
+
+use super::{relative, wrapped, Error, ErrorKind, Metadata};
+
+const HEADER: &str = "#Life 1.05";
+
+/// A pattern read from a Life 1.05 file
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Life105<'a> {
+    /// Description lines, stored as comments
+    pub metadata: Metadata<'a>,
+
+    /// Rule declared with `#N` or `#R`, if any
+    pub rule: Option<Rule>,
+
+    pub world: World,
+}
+
+/// Parse a pattern in Life 1.05 format
+///
+/// Positions of blocks are relative to the origin, and cells wrap around the
+/// edges of the world.
+/// Rules are written with `#N` for Conway's Game of Life, or with `#R` in
+/// S/B notation, e.g. `#R 23/3`.
+pub fn parse(input: &str, origin: (i16, i16)) -> Result<Life105<'_>, Error> {
+    let mut lines = input.lines().zip(1..);
+    match lines.next() {
+        Some((line, _)) if line.trim_end() == HEADER => {}
+        _ => return Err(Error::new(ErrorKind::MissingHeader, 1, 1)),
+    }
+
+    let mut metadata = Metadata::new();
+    let mut rule = None;
+    let mut world = World::new();
+    let mut position = (0, 0);
+    let mut row: i64 = 0;
+
+    for (line, number) in lines {
+        let line = line.trim_end();
+        if let Some(directive) = line.strip_prefix('#') {
+            let mut characters = directive.chars();
+            let kind = characters.next();
+            let value = characters.as_str().trim();
+            let value_column = value.as_ptr() as usize - line.as_ptr() as usize + 1;
+            match kind {
+                Some('D') => metadata.push_comment(value),
+                Some('N') => rule = Some(Rule::CONWAY),
+                Some('R') => {
+                    let parsed = Rule::parse(value);
+                    let error = Error::new(ErrorKind::InvalidRule, number, value_column);
+                    rule = Some(parsed.ok_or(error)?);
+                }
+                Some('P') => {
+                    position = parse_position(line, value, number)?;
+                    row = 0;
+                }
+                _ => {}
+            }
+            continue;
+        }
+
+        for (offset, character) in line.char_indices() {
+            match character {
+                '.' => {}
+                '*' => {
+                    let x = position.0 as i64 + offset as i64;
+                    let (x, y) = wrapped(x, position.1 as i64 + row, origin);
+                    world.set_cell(x, y, true);
+                }
+                character => {
+                    let kind = ErrorKind::UnexpectedCharacter(character);
+                    return Err(Error::new(kind, number, offset + 1));
+                }
+            }
+        }
+        row += 1;
+    }
+
+    Ok(Life105 {
+        metadata,
+        rule,
+        world,
+    })
+}
+
+/// Write the live cells of a world in Life 1.05 format
+///
+/// The cells are written as a single block covering their bounding box,
+/// whose position is relative to the origin.
+pub fn write<W>(
+    out: &mut W,
+    world: &World,
+    rule: Rule,
+    metadata: &Metadata<'_>,
+    origin: (i16, i16),
+) -> fmt::Result
+where
+    W: fmt::Write,
+{
+    writeln!(out, "{}", HEADER)?;
+    for comment in metadata.comments() {
+        writeln!(out, "#D {}", comment)?;
+    }
+    if rule == Rule::CONWAY {
+        writeln!(out, "#N")?;
+    } else {
+        out.write_str("#R ")?;
+        for count in Rule::counts(rule.survival()) {
+            write!(out, "{}", count)?;
+        }
+        out.write_char('/')?;
+        for count in Rule::counts(rule.birth()) {
+            write!(out, "{}", count)?;
+        }
+        writeln!(out)?;
+    }
+
+    if let Some(region) = world.bounding_box() {
+        let (x, y) = relative(region.x, region.y, origin);
+        writeln!(out, "#P {} {}", x, y)?;
+        let pattern = world.extract_pattern(region);
+        for y in 0..pattern.height() {
+            for x in 0..pattern.width() {
+                out.write_char(if pattern.get_cell(x, y) { '*' } else { '.' })?;
+            }
+            writeln!(out)?;
+        }
+    }
+    Ok(())
+}
+
+/// Parse the coordinates of a `#P` line
+fn parse_position(line: &str, value: &str, number: usize) -> Result<(i32, i32), Error> {
+    let mut tokens = value.split_whitespace();
+    let mut coordinate = || {
+        let token = tokens.next();
+        let column = match token {
+            Some(token) => token.as_ptr() as usize - line.as_ptr() as usize + 1,
+            None => line.len() + 1,
+        };
+        token
+            .and_then(|token| token.parse::<i32>().ok())
+            .ok_or_else(|| Error::new(ErrorKind::InvalidNumber, number, column))
+    };
+    Ok((coordinate()?, coordinate()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GLIDER: &str = "#Life 1.05
+#D The smallest spaceship
+#N
+#P -1 -1
+.*.
+..*
+***
+";
+
+    #[test]
+    fn parse_glider() {
+        let life = parse(GLIDER, (10, 5)).unwrap();
+
+        let mut expected = World::new();
+        expected.set_cell(10, 4, true);
+        expected.set_cell(11, 5, true);
+        expected.set_cell(9, 6, true);
+        expected.set_cell(10, 6, true);
+        expected.set_cell(11, 6, true);
+
+        assert_eq!(life.world, expected);
+        assert_eq!(life.rule, Some(Rule::CONWAY));
+        assert_eq!(
+            life.metadata.comments().next(),
+            Some("The smallest spaceship")
+        );
+    }
+
+    #[test]
+    fn parse_blocks_and_rule() {
+        let input = "#Life 1.05\n#R 23/36\n#P 0 0\n**\n#P -3 2\n*\n.*\n";
+
+        let life = parse(input, (0, 0)).unwrap();
+
+        assert_eq!(life.rule, Rule::parse("B36/S23"));
+        assert!(life.world.get_cell(0, 0));
+        assert!(life.world.get_cell(1, 0));
+        assert!(life.world.get_cell(World::WIDTH as i16 - 3, 2));
+        assert!(life.world.get_cell(World::WIDTH as i16 - 2, 3));
+        assert_eq!(life.world.population(), 4);
+    }
+
+    #[test]
+    fn positions_at_the_limits() {
+        let input = "#Life 1.05\n#P 2147483647 2147483647\n.*\n*\n";
+
+        let life = parse(input, (0, 0)).unwrap();
+
+        let max = i32::MAX as i64;
+        let wrap = |value: i64, size: usize| (value % size as i64) as i16;
+        let (x, y) = (wrap(max, World::WIDTH), wrap(max, World::HEIGHT));
+        let (next_x, next_y) = (wrap(max + 1, World::WIDTH), wrap(max + 1, World::HEIGHT));
+        assert!(life.world.get_cell(next_x, y));
+        assert!(life.world.get_cell(x, next_y));
+        assert_eq!(life.world.population(), 2);
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            parse("#Life 1.06\n", (0, 0)),
+            Err(Error::new(ErrorKind::MissingHeader, 1, 1))
+        );
+        assert_eq!(
+            parse("#Life 1.05\n#R 23/9\n", (0, 0)),
+            Err(Error::new(ErrorKind::InvalidRule, 2, 4))
+        );
+        assert_eq!(
+            parse("#Life 1.05\n#P 1 a\n", (0, 0)),
+            Err(Error::new(ErrorKind::InvalidNumber, 2, 6))
+        );
+        assert_eq!(
+            parse("#Life 1.05\n#P 1 1\n.*o\n", (0, 0)),
+            Err(Error::new(ErrorKind::UnexpectedCharacter('o'), 3, 3))
+        );
+    }
+
+    #[test]
+    fn round_trip() {
+        let life = parse(GLIDER, (10, 5)).unwrap();
+
+        let mut output = String::new();
+        write(
+            &mut output,
+            &life.world,
+            Rule::CONWAY,
+            &life.metadata,
+            (10, 5),
+        )
+        .unwrap();
+
+        assert_eq!(output, GLIDER);
+    }
+
+    #[test]
+    fn write_rule() {
+        let mut output = String::new();
+        let rule = Rule::parse("B36/S23").unwrap();
+        write(&mut output, &World::new(), rule, &Metadata::new(), (0, 0)).unwrap();
+
+        assert_eq!(output, "#Life 1.05\n#R 23/36\n");
+    }
+}
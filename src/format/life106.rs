@@ -0,0 +1,155 @@
+// Copyright Claudio Mattera 2021.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Life 1.06 format
+//!
+//! This format is a list of coordinates of live cells, e.g. a glider is
+//! written as follows.
+//!
+//! ~~~~text
+//! #Life 1.06
+//! 0 -1
+//! 1 0
+//! -1 1
+//! 0 1
+//! 1 1
+//! ~~~~
+
+use core::fmt;
+
+use crate::game::World;
+
+use super::{relative, wrapped, Error, ErrorKind};
+
+const HEADER: &str = "#Life 1.06";
+
+/// Parse a pattern in Life 1.06 format
+///
+/// Coordinates are relative to the origin, and wrap around the edges of the
+/// world.
+pub fn parse(input: &str, origin: (i16, i16)) -> Result<World, Error> {
+    let mut lines = input.lines().zip(1..);
+    match lines.next() {
+        Some((line, _)) if line.trim_end() == HEADER => {}
+        _ => return Err(Error::new(ErrorKind::MissingHeader, 1, 1)),
+    }
+
+    let mut world = World::new();
+    for (line, number) in lines {
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let mut tokens = line.split_whitespace();
+        let mut coordinate = || {
+            let token = tokens.next();
+            let column = match token {
+                Some(token) => token.as_ptr() as usize - line.as_ptr() as usize + 1,
+                None => line.len() + 1,
+            };
+            token
+                .and_then(|token| token.parse::<i32>().ok())
+                .ok_or_else(|| Error::new(ErrorKind::InvalidNumber, number, column))
+        };
+        let x = coordinate()?;
+        let y = coordinate()?;
+        if let Some(token) = tokens.next() {
+            let column = token.as_ptr() as usize - line.as_ptr() as usize + 1;
+            let character = token.chars().next().unwrap_or(' ');
+            let kind = ErrorKind::UnexpectedCharacter(character);
+            return Err(Error::new(kind, number, column));
+        }
+
+        let (x, y) = wrapped(x.into(), y.into(), origin);
+        world.set_cell(x, y, true);
+    }
+
+    Ok(world)
+}
+
+/// Write the live cells of a world in Life 1.06 format
+///
+/// Coordinates are written relative to the origin, in the range from minus
+/// half to plus half the size of the world.
+pub fn write<W>(out: &mut W, world: &World, origin: (i16, i16)) -> fmt::Result
+where
+    W: fmt::Write,
+{
+    writeln!(out, "{}", HEADER)?;
+    for y in 0..(World::HEIGHT as i16) {
+        for x in 0..(World::WIDTH as i16) {
+            if world.get_cell(x, y) {
+                let (x, y) = relative(x, y, origin);
+                writeln!(out, "{} {}", x, y)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GLIDER: &str = "#Life 1.06\n0 -1\n1 0\n-1 1\n0 1\n1 1\n";
+
+    #[test]
+    fn parse_glider() {
+        let world = parse(GLIDER, (10, 5)).unwrap();
+
+        let mut expected = World::new();
+        expected.set_cell(10, 4, true);
+        expected.set_cell(11, 5, true);
+        expected.set_cell(9, 6, true);
+        expected.set_cell(10, 6, true);
+        expected.set_cell(11, 6, true);
+
+        assert_eq!(world, expected);
+    }
+
+    #[test]
+    fn parse_across_the_edges() {
+        let world = parse("#Life 1.06\n-1 -1\n40 20\n", (0, 0)).unwrap();
+
+        assert!(world.get_cell(World::WIDTH as i16 - 1, World::HEIGHT as i16 - 1));
+        assert!(world.get_cell(2, 2));
+        assert_eq!(world.population(), 2);
+    }
+
+    #[test]
+    fn missing_header() {
+        let actual = parse("0 0\n", (0, 0));
+
+        assert_eq!(actual, Err(Error::new(ErrorKind::MissingHeader, 1, 1)));
+    }
+
+    #[test]
+    fn invalid_coordinates() {
+        assert_eq!(
+            parse("#Life 1.06\n0 0\n1 x\n", (0, 0)),
+            Err(Error::new(ErrorKind::InvalidNumber, 3, 3))
+        );
+        assert_eq!(
+            parse("#Life 1.06\n7\n", (0, 0)),
+            Err(Error::new(ErrorKind::InvalidNumber, 2, 2))
+        );
+        assert_eq!(
+            parse("#Life 1.06\n1 2 3\n", (0, 0)),
+            Err(Error::new(ErrorKind::UnexpectedCharacter('3'), 2, 5))
+        );
+    }
+
+    #[test]
+    fn round_trip() {
+        let world = parse(GLIDER, (10, 5)).unwrap();
+
+        let mut output = String::new();
+        write(&mut output, &world, (10, 5)).unwrap();
+
+        assert_eq!(output, GLIDER);
+        assert_eq!(parse(&output, (10, 5)).unwrap(), world);
+    }
+}
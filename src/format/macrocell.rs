@@ -0,0 +1,553 @@
+// Copyright Claudio Mattera 2021.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Macrocell format
+//!
+//! This is the quadtree format used by Golly for large patterns.
+//! Each line after the header defines a node, which is either a leaf of 8×8
+//! cells written as rows of `.` and `*` terminated by `$`, or a square made
+//! of four nodes of the previous level, referred to by their line number
+//! among the nodes.
+//! Node `0` is an empty square of any size, and the last node is the root.
+//!
+//! ~~~~text
+//! [M2] (golly 4.0)
+//! #R B3/S23
+//! .*$..*$***$
+//! 4 1 0 0 0
+//! ~~~~
+//!
+//! Patterns are kept as a quadtree, which is only available on the host
+//! since it requires allocation, and can be cropped into a world.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::string::String;
+use std::vec::Vec;
+
+use crate::game::{Rule, World};
+
+use super::{Error, ErrorKind};
+
+const HEADER: &str = "[M2]";
+
+/// Level of leaves, which are squares of 8×8 cells
+const LEAF_LEVEL: u8 = 3;
+
+/// Maximal level of the root, so that coordinates fit in 64 bits
+const MAX_LEVEL: u8 = 63;
+
+/// A node of the quadtree
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum Node {
+    /// An empty square of any level
+    Empty,
+
+    /// A square of 8×8 cells, with bit `y * 8 + x` set for live cells
+    Leaf(u64),
+
+    /// A square made of four nodes of the previous level, in order
+    /// north-west, north-east, south-west and south-east
+    Inner { level: u8, children: [usize; 4] },
+}
+
+impl Node {
+    fn level(&self) -> Option<u8> {
+        match self {
+            Node::Empty => None,
+            Node::Leaf(_) => Some(LEAF_LEVEL),
+            Node::Inner { level, .. } => Some(*level),
+        }
+    }
+}
+
+/// A pattern of arbitrary size stored as a quadtree
+///
+/// Identical subtrees are stored only once, so very large but regular
+/// patterns take little memory.
+/// Coordinates are counted from the top-left corner of the root square.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Macrocell {
+    /// All nodes, children always precede their parents
+    nodes: Vec<Node>,
+    root: usize,
+
+    /// Rule declared with `#R`, if any
+    pub rule: Option<Rule>,
+
+    /// Lines of comments declared with `#C` or `#N`
+    pub comments: Vec<String>,
+}
+
+impl Macrocell {
+    /// Create a quadtree from the cells of a world
+    ///
+    /// The world is placed at the top-left corner of the smallest square
+    /// containing it.
+    pub fn from_world(world: &World) -> Self {
+        let mut level = LEAF_LEVEL;
+        while 1 << level < World::WIDTH.max(World::HEIGHT) {
+            level += 1;
+        }
+
+        let mut macrocell = Self {
+            nodes: vec![Node::Empty],
+            root: 0,
+            rule: None,
+            comments: Vec::new(),
+        };
+        let mut known = HashMap::new();
+        macrocell.root = macrocell.build(world, level, 0, 0, &mut known);
+        macrocell
+    }
+
+    /// Get the level of the root, which is a square with side `2^level`
+    pub fn level(&self) -> u8 {
+        self.nodes[self.root].level().unwrap_or(LEAF_LEVEL)
+    }
+
+    pub fn get_cell(&self, x: u64, y: u64) -> bool {
+        let level = self.level();
+        if level < 64 && (x >> level != 0 || y >> level != 0) {
+            return false;
+        }
+
+        let mut index = self.root;
+        let (mut x, mut y) = (x, y);
+        loop {
+            match self.nodes[index] {
+                Node::Empty => return false,
+                Node::Leaf(bits) => return bits & (1 << (y * 8 + x)) != 0,
+                Node::Inner { level, children } => {
+                    let half = 1 << (level - 1);
+                    let quadrant = (x >= half) as usize + 2 * (y >= half) as usize;
+                    x %= half;
+                    y %= half;
+                    index = children[quadrant];
+                }
+            }
+        }
+    }
+
+    /// Count the live cells
+    pub fn population(&self) -> u64 {
+        let mut populations = vec![0u64; self.nodes.len()];
+        for (index, node) in self.nodes.iter().enumerate() {
+            populations[index] = match node {
+                Node::Empty => 0,
+                Node::Leaf(bits) => bits.count_ones() as u64,
+                Node::Inner { children, .. } => children
+                    .iter()
+                    .map(|child| populations[*child])
+                    .fold(0, u64::saturating_add),
+            };
+        }
+        populations[self.root]
+    }
+
+    /// Compute the smallest rectangle containing all live cells
+    ///
+    /// The rectangle is returned as its top-left corner and its size.
+    pub fn bounding_box(&self) -> Option<(u64, u64, u64, u64)> {
+        let mut boxes: Vec<Option<(u64, u64, u64, u64)>> = vec![None; self.nodes.len()];
+        for (index, node) in self.nodes.iter().enumerate() {
+            boxes[index] = match *node {
+                Node::Empty => None,
+                Node::Leaf(bits) => (0..64u64)
+                    .filter(|i| bits & (1 << i) != 0)
+                    .map(|i| (i % 8, i / 8, i % 8, i / 8))
+                    .reduce(union),
+                Node::Inner { level, children } => {
+                    let half = 1u64 << (level - 1);
+                    children
+                        .iter()
+                        .enumerate()
+                        .filter_map(|(quadrant, child)| {
+                            let dx = half * (quadrant % 2) as u64;
+                            let dy = half * (quadrant / 2) as u64;
+                            boxes[*child]
+                                .map(|(x0, y0, x1, y1)| (x0 + dx, y0 + dy, x1 + dx, y1 + dy))
+                        })
+                        .reduce(union)
+                }
+            };
+        }
+        boxes[self.root].map(|(x0, y0, x1, y1)| (x0, y0, x1 - x0 + 1, y1 - y0 + 1))
+    }
+
+    /// Crop a window of the size of the world with its top-left corner at the
+    /// given coordinates
+    pub fn crop(&self, x: u64, y: u64) -> World {
+        let mut world = World::new();
+        for dy in 0..World::HEIGHT {
+            for dx in 0..World::WIDTH {
+                let alive = match (x.checked_add(dx as u64), y.checked_add(dy as u64)) {
+                    (Some(x), Some(y)) => self.get_cell(x, y),
+                    _ => false,
+                };
+                world.set_cell(dx as i16, dy as i16, alive);
+            }
+        }
+        world
+    }
+
+    /// Crop a window of the size of the world centred on the live cells
+    pub fn crop_centre(&self) -> World {
+        match self.bounding_box() {
+            Some((x, y, width, height)) => {
+                let x = (x + width / 2).saturating_sub(World::WIDTH as u64 / 2);
+                let y = (y + height / 2).saturating_sub(World::HEIGHT as u64 / 2);
+                self.crop(x, y)
+            }
+            None => World::new(),
+        }
+    }
+
+    /// Build the node for a square of the world, reusing identical nodes
+    fn build(
+        &mut self,
+        world: &World,
+        level: u8,
+        x: usize,
+        y: usize,
+        known: &mut HashMap<Node, usize>,
+    ) -> usize {
+        let node = if level == LEAF_LEVEL {
+            let mut bits = 0u64;
+            for dy in 0..8 {
+                for dx in 0..8 {
+                    let (cx, cy) = (x + dx, y + dy);
+                    if cx < World::WIDTH
+                        && cy < World::HEIGHT
+                        && world.get_cell(cx as i16, cy as i16)
+                    {
+                        bits |= 1 << (dy * 8 + dx);
+                    }
+                }
+            }
+            Node::Leaf(bits)
+        } else {
+            let half = 1 << (level - 1);
+            let mut children = [0; 4];
+            for (quadrant, child) in children.iter_mut().enumerate() {
+                let cx = x + half * (quadrant % 2);
+                let cy = y + half * (quadrant / 2);
+                *child = self.build(world, level - 1, cx, cy, known);
+            }
+            Node::Inner { level, children }
+        };
+
+        if node == Node::Leaf(0)
+            || node
+                == (Node::Inner {
+                    level,
+                    children: [0; 4],
+                })
+        {
+            return 0;
+        }
+        *known.entry(node).or_insert_with(|| {
+            self.nodes.push(node);
+            self.nodes.len() - 1
+        })
+    }
+}
+
+/// Parse a pattern in Macrocell format
+///
+/// Only two-state patterns are supported.
+pub fn parse(input: &str) -> Result<Macrocell, Error> {
+    let mut lines = input.lines().zip(1..);
+    match lines.next() {
+        Some((line, _)) if line.starts_with(HEADER) => {}
+        _ => return Err(Error::new(ErrorKind::MissingHeader, 1, 1)),
+    }
+
+    let mut macrocell = Macrocell {
+        nodes: vec![Node::Empty],
+        root: 0,
+        rule: None,
+        comments: Vec::new(),
+    };
+
+    for (line, number) in lines {
+        let line = line.trim_end();
+        if line.is_empty() {
+            continue;
+        }
+        if let Some(directive) = line.strip_prefix('#') {
+            let mut characters = directive.chars();
+            let kind = characters.next();
+            let value = characters.as_str().trim();
+            match kind {
+                Some('R') => {
+                    let column = value.as_ptr() as usize - line.as_ptr() as usize + 1;
+                    let rule = Rule::parse(value)
+                        .ok_or_else(|| Error::new(ErrorKind::InvalidRule, number, column))?;
+                    macrocell.rule = Some(rule);
+                }
+                Some('C') | Some('N') => macrocell.comments.push(value.into()),
+                _ => {}
+            }
+            continue;
+        }
+
+        let node = if line.starts_with(|character: char| character.is_ascii_digit()) {
+            parse_inner(line, number, &macrocell.nodes)?
+        } else {
+            parse_leaf(line, number)?
+        };
+        macrocell.nodes.push(node);
+    }
+
+    macrocell.root = macrocell.nodes.len() - 1;
+    Ok(macrocell)
+}
+
+/// Write a pattern in Macrocell format
+pub fn write<W>(out: &mut W, macrocell: &Macrocell) -> fmt::Result
+where
+    W: fmt::Write,
+{
+    writeln!(out, "{} (game-of-life)", HEADER)?;
+    if let Some(rule) = macrocell.rule {
+        writeln!(out, "#R {}", rule)?;
+    }
+    for comment in &macrocell.comments {
+        writeln!(out, "#C {}", comment)?;
+    }
+
+    // Only nodes reachable from the root are written, renumbered in order
+    let mut reachable = vec![false; macrocell.nodes.len()];
+    reachable[macrocell.root] = true;
+    for index in (1..macrocell.nodes.len()).rev() {
+        if let Node::Inner { children, .. } = macrocell.nodes[index] {
+            if reachable[index] {
+                for child in children.iter() {
+                    reachable[*child] = true;
+                }
+            }
+        }
+    }
+
+    let mut numbers = vec![0; macrocell.nodes.len()];
+    let mut next = 1;
+    for (index, node) in macrocell.nodes.iter().enumerate().skip(1) {
+        if !reachable[index] {
+            continue;
+        }
+        numbers[index] = next;
+        next += 1;
+
+        match *node {
+            Node::Empty => unreachable!("Empty nodes are never stored after the first"),
+            Node::Leaf(bits) => {
+                let rows = (0..8).rev().find(|y| (bits >> (y * 8)) & 0xff != 0);
+                for y in 0..=rows.unwrap_or(0) {
+                    let row = (bits >> (y * 8)) & 0xff;
+                    let width = 64 - row.leading_zeros() as usize;
+                    for x in 0..width {
+                        out.write_char(if row & (1 << x) != 0 { '*' } else { '.' })?;
+                    }
+                    out.write_char('$')?;
+                }
+                writeln!(out)?;
+            }
+            Node::Inner { level, children } => {
+                write!(out, "{}", level)?;
+                for child in children.iter() {
+                    write!(out, " {}", numbers[*child])?;
+                }
+                writeln!(out)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn parse_leaf(line: &str, number: usize) -> Result<Node, Error> {
+    let mut bits = 0u64;
+    let (mut x, mut y) = (0, 0);
+    for (offset, character) in line.char_indices() {
+        let error = |kind| Error::new(kind, number, offset + 1);
+        match character {
+            '.' | '*' if x >= 8 || y >= 8 => return Err(error(ErrorKind::OutOfBounds)),
+            '.' => x += 1,
+            '*' => {
+                bits |= 1 << (y * 8 + x);
+                x += 1;
+            }
+            '$' => {
+                x = 0;
+                y += 1;
+            }
+            character => return Err(error(ErrorKind::UnexpectedCharacter(character))),
+        }
+    }
+    Ok(Node::Leaf(bits))
+}
+
+fn parse_inner(line: &str, number: usize, nodes: &[Node]) -> Result<Node, Error> {
+    let mut tokens = line.split_whitespace().map(|token| {
+        let column = token.as_ptr() as usize - line.as_ptr() as usize + 1;
+        (token.parse::<usize>().ok(), column)
+    });
+
+    let level = match tokens.next() {
+        Some((Some(level), _)) if level > LEAF_LEVEL as usize && level <= MAX_LEVEL as usize => {
+            level as u8
+        }
+        _ => return Err(Error::new(ErrorKind::InvalidNumber, number, 1)),
+    };
+
+    let mut children = [0; 4];
+    for child in children.iter_mut() {
+        let (index, column) = tokens.next().unwrap_or((None, line.len() + 1));
+        let error = |kind| Error::new(kind, number, column);
+        let index = index.ok_or_else(|| error(ErrorKind::InvalidNumber))?;
+        match nodes.get(index).map(Node::level) {
+            Some(None) => {}
+            Some(Some(child_level)) if child_level + 1 == level => {}
+            _ => return Err(error(ErrorKind::InvalidReference)),
+        }
+        *child = index;
+    }
+
+    if let Some((_, column)) = tokens.next() {
+        return Err(Error::new(ErrorKind::InvalidNumber, number, column));
+    }
+    Ok(Node::Inner { level, children })
+}
+
+fn union(first: (u64, u64, u64, u64), second: (u64, u64, u64, u64)) -> (u64, u64, u64, u64) {
+    (
+        first.0.min(second.0),
+        first.1.min(second.1),
+        first.2.max(second.2),
+        first.3.max(second.3),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GLIDER: &str = "[M2] (golly 4.0)
+#R B3/S23
+#C A glider
+.*$..*$***$
+4 1 0 0 0
+";
+
+    #[test]
+    fn parse_glider() {
+        let macrocell = parse(GLIDER).unwrap();
+
+        assert_eq!(macrocell.rule, Some(Rule::CONWAY));
+        assert_eq!(macrocell.comments, vec!["A glider".to_string()]);
+        assert_eq!(macrocell.level(), 4);
+        assert_eq!(macrocell.population(), 5);
+        assert_eq!(macrocell.bounding_box(), Some((0, 0, 3, 3)));
+        assert!(macrocell.get_cell(1, 0));
+        assert!(macrocell.get_cell(2, 2));
+        assert!(!macrocell.get_cell(100, 100));
+    }
+
+    #[test]
+    fn large_sparse_pattern() {
+        // Two gliders at opposite corners of a square of side 2^40
+        let mut input = String::from("[M2]\n.*$..*$***$\n");
+        input.push_str("4 1 0 0 0\n4 0 0 0 1\n");
+        let mut previous = (2, 3);
+        let mut next = 4;
+        for level in 5..=40 {
+            input.push_str(&format!("{} {} 0 0 0\n", level, previous.0));
+            input.push_str(&format!("{} 0 0 0 {}\n", level, previous.1));
+            previous = (next, next + 1);
+            next += 2;
+        }
+        input.push_str(&format!("41 {} 0 0 {}\n", previous.0, previous.1));
+
+        let macrocell = parse(&input).unwrap();
+
+        assert_eq!(macrocell.level(), 41);
+        assert_eq!(macrocell.population(), 10);
+        let side = 1u64 << 41;
+        let (x, y, width, height) = macrocell.bounding_box().unwrap();
+        assert_eq!((x, y), (0, 0));
+        assert_eq!((width, height), (side - 5, side - 5));
+
+        let world = macrocell.crop(side - 16, side - 16);
+        assert_eq!(world.population(), 5);
+        assert!(world.get_cell(8 + 1, 8));
+    }
+
+    #[test]
+    fn crop_centre() {
+        let macrocell = parse(GLIDER).unwrap();
+
+        let world = macrocell.crop_centre();
+
+        assert_eq!(world.population(), 5);
+        assert_eq!(world.pattern(), macrocell.crop(0, 0).pattern());
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            parse("x = 3, y = 3\n"),
+            Err(Error::new(ErrorKind::MissingHeader, 1, 1))
+        );
+        assert_eq!(
+            parse("[M2]\n.*$..*$***$\n4 1 0 0 7\n"),
+            Err(Error::new(ErrorKind::InvalidReference, 3, 9))
+        );
+        assert_eq!(
+            parse("[M2]\n.*$..*$***$\n5 1 0 0 0\n"),
+            Err(Error::new(ErrorKind::InvalidReference, 3, 3))
+        );
+        assert_eq!(
+            parse("[M2]\n.*$..o$\n"),
+            Err(Error::new(ErrorKind::UnexpectedCharacter('o'), 2, 6))
+        );
+        assert_eq!(
+            parse("[M2]\n.........*$\n"),
+            Err(Error::new(ErrorKind::OutOfBounds, 2, 9))
+        );
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut world = World::new();
+        for x in 0..(World::WIDTH as i16) {
+            world.set_cell(x, 3, true);
+            world.set_cell(x, 11, true);
+        }
+        world.set_cell(37, 17, true);
+
+        let mut macrocell = Macrocell::from_world(&world);
+        macrocell.rule = Some(Rule::CONWAY);
+
+        let mut output = String::new();
+        write(&mut output, &macrocell).unwrap();
+        let parsed = parse(&output).unwrap();
+
+        assert_eq!(parsed.crop(0, 0), world);
+        assert_eq!(parsed.population(), world.population() as u64);
+        assert_eq!(parsed.rule, Some(Rule::CONWAY));
+    }
+
+    #[test]
+    fn write_glider() {
+        let macrocell = parse(GLIDER).unwrap();
+
+        let mut output = String::new();
+        write(&mut output, &macrocell).unwrap();
+
+        let expected = "[M2] (game-of-life)\n#R B3/S23\n#C A glider\n.*$..*$***$\n4 1 0 0 0\n";
+        assert_eq!(output, expected);
+    }
+}
@@ -0,0 +1,51 @@
+// Copyright Claudio Mattera 2021.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Metadata of patterns
+
+/// Name, author and comments of a pattern
+///
+/// Strings are borrowed from the pattern file, so no allocation is needed.
+/// At most [`Metadata::MAX_COMMENTS`] comment lines are kept, additional
+/// lines are ignored.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Metadata<'a> {
+    pub name: Option<&'a str>,
+    pub author: Option<&'a str>,
+    comments: [&'a str; Metadata::MAX_COMMENTS],
+    length: usize,
+}
+
+impl<'a> Metadata<'a> {
+    pub const MAX_COMMENTS: usize = 16;
+
+    pub const fn new() -> Self {
+        Self {
+            name: None,
+            author: None,
+            comments: [""; Self::MAX_COMMENTS],
+            length: 0,
+        }
+    }
+
+    /// Append a comment line
+    pub fn push_comment(&mut self, comment: &'a str) {
+        if self.length < Self::MAX_COMMENTS {
+            self.comments[self.length] = comment;
+            self.length += 1;
+        }
+    }
+
+    pub fn comments(&self) -> impl Iterator<Item = &'a str> + '_ {
+        self.comments[..self.length].iter().copied()
+    }
+}
+
+impl Default for Metadata<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,445 @@
+// Copyright Claudio Mattera 2021.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Run Length Encoded format
+//!
+//! This is the format used by Golly and LifeWiki, e.g. a glider is written
+//! as follows.
+//!
+//! ~~~~text
+//! #N Glider
+//! #C The smallest spaceship
+//! x = 3, y = 3, rule = B3/S23
+//! bo$2bo$3o!
+//! ~~~~
+
+use core::fmt;
+
+use crate::game::{Pattern, Rule, World};
+
+use super::{Error, ErrorKind, Metadata};
+
+/// Maximal length of lines of encoded cells
+const LINE_LENGTH: usize = 70;
+
+/// A pattern read from a RLE file
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Rle<'a> {
+    pub metadata: Metadata<'a>,
+
+    /// Rule declared in the header, if any
+    pub rule: Option<Rule>,
+
+    pub pattern: Pattern,
+}
+
+impl Rle<'_> {
+    /// Create a world with the pattern in its centre
+    pub fn world(&self) -> World {
+        World::from_pattern(&self.pattern)
+    }
+}
+
+/// Parse a pattern in RLE format
+///
+/// Lines starting with `#N`, `#O` and `#C` (or `#c`) are read as name,
+/// author and comments, other `#` lines are ignored.
+/// Everything after the final `!` is ignored.
+pub fn parse(input: &str) -> Result<Rle<'_>, Error> {
+    let mut metadata = Metadata::new();
+    let mut lines = input.lines().zip(1..);
+
+    let (header, header_number) = loop {
+        match lines.next() {
+            None => {
+                let line = input.lines().count() + 1;
+                return Err(Error::new(ErrorKind::MissingHeader, line, 1));
+            }
+            Some((line, number)) => {
+                let trimmed = line.trim();
+                if trimmed.is_empty() {
+                    continue;
+                }
+                if let Some(rest) = trimmed.strip_prefix('#') {
+                    read_metadata(rest, &mut metadata);
+                    continue;
+                }
+                break (line, number);
+            }
+        }
+    };
+
+    let (width, height, rule) = parse_header(header, header_number)?;
+    let mut pattern = Pattern::new(width, height);
+
+    let mut x: usize = 0;
+    let mut y: usize = 0;
+    let mut run: Option<usize> = None;
+    for (line, number) in lines {
+        for (offset, character) in line.char_indices() {
+            let error = |kind| Error::new(kind, number, offset + 1);
+            let count = run.unwrap_or(1);
+            match character {
+                '0'..='9' => {
+                    let digit = character as usize - '0' as usize;
+                    let value = run
+                        .unwrap_or(0)
+                        .checked_mul(10)
+                        .and_then(|value| value.checked_add(digit))
+                        .ok_or_else(|| error(ErrorKind::InvalidNumber))?;
+                    run = Some(value);
+                    continue;
+                }
+                'b' | '.' => {
+                    x = x
+                        .checked_add(count)
+                        .ok_or_else(|| error(ErrorKind::OutOfBounds))?;
+                }
+                'o' => {
+                    let end = x
+                        .checked_add(count)
+                        .filter(|end| *end <= width && y < height)
+                        .ok_or_else(|| error(ErrorKind::OutOfBounds))?;
+                    for x in x..end {
+                        pattern.set_cell(x, y, true);
+                    }
+                    x = end;
+                }
+                '$' => {
+                    x = 0;
+                    y = y
+                        .checked_add(count)
+                        .ok_or_else(|| error(ErrorKind::OutOfBounds))?;
+                }
+                '!' => {
+                    return Ok(Rle {
+                        metadata,
+                        rule,
+                        pattern,
+                    })
+                }
+                character if character.is_whitespace() => continue,
+                character => return Err(error(ErrorKind::UnexpectedCharacter(character))),
+            }
+            run = None;
+        }
+    }
+
+    Ok(Rle {
+        metadata,
+        rule,
+        pattern,
+    })
+}
+
+/// Write a pattern in RLE format
+///
+/// Trailing dead cells in each row are omitted, and lines of encoded cells
+/// are at most 70 characters long.
+pub fn write<W>(out: &mut W, pattern: &Pattern, rule: Rule, metadata: &Metadata<'_>) -> fmt::Result
+where
+    W: fmt::Write,
+{
+    if let Some(name) = metadata.name {
+        writeln!(out, "#N {}", name)?;
+    }
+    if let Some(author) = metadata.author {
+        writeln!(out, "#O {}", author)?;
+    }
+    for comment in metadata.comments() {
+        writeln!(out, "#C {}", comment)?;
+    }
+    writeln!(
+        out,
+        "x = {}, y = {}, rule = {}",
+        pattern.width(),
+        pattern.height(),
+        rule
+    )?;
+
+    let mut writer = LineWriter { out, length: 0 };
+    let mut pending_rows = 0;
+    for y in 0..pattern.height() {
+        let mut x = 0;
+        while x < pattern.width() {
+            let alive = pattern.get_cell(x, y);
+            let start = x;
+            while x < pattern.width() && pattern.get_cell(x, y) == alive {
+                x += 1;
+            }
+            if !alive && x == pattern.width() {
+                break;
+            }
+            if pending_rows > 0 {
+                writer.push(pending_rows, '$')?;
+                pending_rows = 0;
+            }
+            writer.push(x - start, if alive { 'o' } else { 'b' })?;
+        }
+        pending_rows += 1;
+    }
+    writer.push(1, '!')?;
+    writeln!(writer.out)
+}
+
+/// Write the live cells of a world in RLE format
+pub fn write_world<W>(
+    out: &mut W,
+    world: &World,
+    rule: Rule,
+    metadata: &Metadata<'_>,
+) -> fmt::Result
+where
+    W: fmt::Write,
+{
+    write(out, &world.pattern(), rule, metadata)
+}
+
+fn read_metadata<'a>(line: &'a str, metadata: &mut Metadata<'a>) {
+    let mut characters = line.chars();
+    let kind = characters.next();
+    let value = characters.as_str().trim();
+    match kind {
+        Some('N') => metadata.name = Some(value),
+        Some('O') => metadata.author = Some(value),
+        Some('C') | Some('c') => metadata.push_comment(value),
+        _ => {}
+    }
+}
+
+/// Parse a header line such as `x = 3, y = 3, rule = B3/S23`
+fn parse_header(line: &str, number: usize) -> Result<(usize, usize, Option<Rule>), Error> {
+    let mut width = None;
+    let mut height = None;
+    let mut rule = None;
+
+    let mut in_rule = false;
+    for part in line.split(',') {
+        let column = part.as_ptr() as usize - line.as_ptr() as usize + 1;
+        let error = |kind| Error::new(kind, number, column);
+        let (key, value) = match part.split_once('=') {
+            Some((key, value)) => (key, value.trim()),
+            // Golly appends the topology to the rule, e.g. `B3/S23:T38,18`
+            None if in_rule => continue,
+            None => return Err(error(ErrorKind::InvalidHeader)),
+        };
+        let value_column = value.as_ptr() as usize - line.as_ptr() as usize + 1;
+        let value_error = |kind| Error::new(kind, number, value_column);
+        in_rule = false;
+        match key.trim() {
+            "x" => {
+                width =
+                    Some(parse_size(value).ok_or_else(|| value_error(ErrorKind::InvalidNumber))?)
+            }
+            "y" => {
+                height =
+                    Some(parse_size(value).ok_or_else(|| value_error(ErrorKind::InvalidNumber))?)
+            }
+            "rule" => {
+                in_rule = true;
+                let notation = value.split(':').next().unwrap_or(value);
+                rule =
+                    Some(Rule::parse(notation).ok_or_else(|| value_error(ErrorKind::InvalidRule))?);
+            }
+            _ => return Err(error(ErrorKind::InvalidHeader)),
+        }
+    }
+
+    match (width, height) {
+        (Some(width), Some(height)) if width > World::WIDTH || height > World::HEIGHT => {
+            Err(Error::new(ErrorKind::TooLarge, number, 1))
+        }
+        (Some(width), Some(height)) => Ok((width, height, rule)),
+        _ => Err(Error::new(ErrorKind::InvalidHeader, number, 1)),
+    }
+}
+
+fn parse_size(value: &str) -> Option<usize> {
+    value.parse().ok()
+}
+
+/// A writer breaking lines of encoded cells
+struct LineWriter<'a, W> {
+    out: &'a mut W,
+    length: usize,
+}
+
+impl<W> LineWriter<'_, W>
+where
+    W: fmt::Write,
+{
+    /// Write a run of cells, preceded by its length if larger than one
+    fn push(&mut self, count: usize, tag: char) -> fmt::Result {
+        let digits = match count {
+            1 => 0,
+            _ => decimal_length(count),
+        };
+        if self.length + digits + 1 > LINE_LENGTH {
+            writeln!(self.out)?;
+            self.length = 0;
+        }
+        if count > 1 {
+            write!(self.out, "{}", count)?;
+        }
+        self.out.write_char(tag)?;
+        self.length += digits + 1;
+        Ok(())
+    }
+}
+
+fn decimal_length(value: usize) -> usize {
+    let mut length = 1;
+    let mut value = value;
+    while value >= 10 {
+        value /= 10;
+        length += 1;
+    }
+    length
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GLIDER: &str = "#N Glider
+#O Richard K. Guy
+#C The smallest, most common, and first discovered spaceship.
+#C www.conwaylife.com/wiki/index.php?title=Glider
+x = 3, y = 3, rule = B3/S23
+bo$2bo$3o!
+";
+
+    #[test]
+    fn parse_glider() {
+        let rle = parse(GLIDER).unwrap();
+
+        assert_eq!(rle.metadata.name, Some("Glider"));
+        assert_eq!(rle.metadata.author, Some("Richard K. Guy"));
+        assert_eq!(rle.metadata.comments().count(), 2);
+        assert_eq!(rle.rule, Some(Rule::CONWAY));
+        assert_eq!((rle.pattern.width(), rle.pattern.height()), (3, 3));
+
+        let cells: Vec<(usize, usize)> = rle.pattern.live_cells().collect();
+        assert_eq!(cells, vec![(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)]);
+    }
+
+    #[test]
+    fn parse_runs_across_lines() {
+        let input = "x=12,y=4\n12o2$\n3b\n2o!";
+
+        let rle = parse(input).unwrap();
+
+        assert_eq!(rle.rule, None);
+        assert_eq!(rle.pattern.population(), 14);
+        assert!(rle.pattern.get_cell(11, 0));
+        assert!(!rle.pattern.get_cell(0, 1));
+        assert!(rle.pattern.get_cell(3, 2));
+        assert!(rle.pattern.get_cell(4, 2));
+    }
+
+    #[test]
+    fn parse_rule_with_topology() {
+        let rle = parse("x = 1, y = 1, rule = B36/S23:T38,18\no!").unwrap();
+
+        assert_eq!(rle.rule, Rule::parse("B36/S23"));
+    }
+
+    #[test]
+    fn missing_header() {
+        let actual = parse("#C Nothing here\n");
+
+        assert_eq!(actual, Err(Error::new(ErrorKind::MissingHeader, 2, 1)));
+    }
+
+    #[test]
+    fn invalid_rule() {
+        let actual = parse("x = 3, y = 3, rule = B9/S23\n3o!");
+
+        assert_eq!(actual, Err(Error::new(ErrorKind::InvalidRule, 1, 22)));
+    }
+
+    #[test]
+    fn unexpected_character() {
+        let actual = parse("#N Test\nx = 3, y = 3\nbo$\n2bx$3o!");
+
+        assert_eq!(
+            actual,
+            Err(Error::new(ErrorKind::UnexpectedCharacter('x'), 4, 3))
+        );
+    }
+
+    #[test]
+    fn cell_out_of_bounds() {
+        let actual = parse("x = 3, y = 1\n4o!");
+
+        assert_eq!(actual, Err(Error::new(ErrorKind::OutOfBounds, 2, 2)));
+    }
+
+    #[test]
+    fn runs_overflowing() {
+        let max = usize::MAX;
+        let cases = [
+            (format!("x = 3, y = 1\n2b{}b!", max), 23),
+            (format!("x = 3, y = 1\n{}bo!", max), 22),
+            (format!("x = 3, y = 1\n{}o!", max), 21),
+            (format!("x = 3, y = 1\n{}${}$!", max, max), 42),
+        ];
+        for (input, column) in cases.iter() {
+            let actual = parse(input);
+
+            assert_eq!(actual, Err(Error::new(ErrorKind::OutOfBounds, 2, *column)));
+        }
+    }
+
+    #[test]
+    fn pattern_too_large() {
+        let actual = parse("x = 100, y = 1\n100o!");
+
+        assert_eq!(actual, Err(Error::new(ErrorKind::TooLarge, 1, 1)));
+    }
+
+    #[test]
+    fn write_glider() {
+        let rle = parse(GLIDER).unwrap();
+        let mut metadata = Metadata::new();
+        metadata.name = Some("Glider");
+        metadata.push_comment("A comment");
+
+        let mut actual = String::new();
+        write(&mut actual, &rle.pattern, Rule::CONWAY, &metadata).unwrap();
+
+        let expected = "#N Glider\n#C A comment\nx = 3, y = 3, rule = B3/S23\nbo$2bo$3o!\n";
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn write_empty_rows_and_long_lines() {
+        let mut world = World::new();
+        for x in (0..(World::WIDTH as i16)).step_by(2) {
+            for y in [0, 1, 5, 6, 7, 17] {
+                world.set_cell(x, y, true);
+            }
+        }
+
+        let mut output = String::new();
+        write_world(&mut output, &world, Rule::CONWAY, &Metadata::new()).unwrap();
+
+        assert!(output.lines().all(|line| line.len() <= LINE_LENGTH));
+        assert!(output.contains("4$"));
+        assert_eq!(parse(&output).unwrap().pattern, world.pattern());
+    }
+
+    #[test]
+    fn round_trip() {
+        let rle = parse(GLIDER).unwrap();
+
+        let mut output = String::new();
+        write(&mut output, &rle.pattern, Rule::CONWAY, &rle.metadata).unwrap();
+        let actual = parse(&output).unwrap();
+
+        assert_eq!(actual, rle);
+    }
+}
@@ -8,5 +8,35 @@
 
 mod r#loop;
 
+mod breakpoint;
+pub use breakpoint::{Breakpoint, Breakpoints};
+
+mod encoding;
+pub use encoding::{decode_world, encode_world, MAX_ENCODED_SIZE};
+
+mod pattern;
+pub use pattern::Pattern;
+
+mod region;
+pub use region::Region;
+
+mod rule;
+pub use rule::Rule;
+
+mod save;
+pub use save::{clear_slot, read_slot, write_slot, LoadError, Save, SLOTS_SIZE, SLOT_COUNT};
+
+mod selection;
+pub use selection::Selection;
+
+mod settings;
+pub use settings::{Settings, SETTINGS_OFFSET};
+
+mod stamp;
+pub use stamp::Stamp;
+
+mod topology;
+pub use topology::Topology;
+
 mod world;
 pub use world::World;
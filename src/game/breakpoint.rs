@@ -0,0 +1,208 @@
+// Copyright Claudio Mattera 2021.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Conditional breakpoints
+
+use super::{Region, World};
+
+/// A condition on the evolution of the world
+///
+/// Breakpoints are triggered by transitions, e.g. the population rising above
+/// a threshold, so that the simulation can be resumed after they fire.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Breakpoint {
+    /// The population rises above a threshold
+    PopulationAbove(usize),
+
+    /// The population drops below a threshold
+    PopulationBelow(usize),
+
+    /// Any cell in a region changes
+    RegionChanged(Region),
+
+    /// A cell becomes alive
+    CellAlive(i16, i16),
+
+    /// A generation is reached
+    Generation(u32),
+}
+
+impl Breakpoint {
+    /// Check whether the breakpoint is triggered by a new generation
+    pub fn is_triggered(&self, previous: &World, current: &World, generation: u32) -> bool {
+        match *self {
+            Breakpoint::PopulationAbove(threshold) => {
+                previous.population() <= threshold && current.population() > threshold
+            }
+            Breakpoint::PopulationBelow(threshold) => {
+                previous.population() >= threshold && current.population() < threshold
+            }
+            Breakpoint::RegionChanged(region) => region
+                .cells()
+                .any(|(x, y)| previous.get_cell(x, y) != current.get_cell(x, y)),
+            Breakpoint::CellAlive(x, y) => !previous.get_cell(x, y) && current.get_cell(x, y),
+            Breakpoint::Generation(target) => generation == target,
+        }
+    }
+}
+
+/// A set of breakpoints
+#[derive(Debug)]
+pub struct Breakpoints {
+    breakpoints: [Option<Breakpoint>; Breakpoints::CAPACITY],
+    length: usize,
+}
+
+impl Breakpoints {
+    pub const CAPACITY: usize = 6;
+
+    pub const fn new() -> Self {
+        Self {
+            breakpoints: [None; Self::CAPACITY],
+            length: 0,
+        }
+    }
+
+    /// Add a breakpoint, returning `false` if there is no space left
+    pub fn add(&mut self, breakpoint: Breakpoint) -> bool {
+        if self.length == Self::CAPACITY {
+            return false;
+        }
+        self.breakpoints[self.length] = Some(breakpoint);
+        self.length += 1;
+        true
+    }
+
+    /// Remove the breakpoint at a given index
+    pub fn remove(&mut self, index: usize) {
+        if index < self.length {
+            self.breakpoints[index..self.length].rotate_left(1);
+            self.length -= 1;
+            self.breakpoints[self.length] = None;
+        }
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::new();
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Breakpoint> {
+        self.breakpoints[..self.length].iter().flatten()
+    }
+
+    pub fn len(&self) -> usize {
+        self.length
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.length == 0
+    }
+
+    /// Find the first breakpoint triggered by a new generation
+    pub fn check(&self, previous: &World, current: &World, generation: u32) -> Option<Breakpoint> {
+        self.iter()
+            .find(|breakpoint| breakpoint.is_triggered(previous, current, generation))
+            .copied()
+    }
+}
+
+impl Default for Breakpoints {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn blinker() -> World {
+        let mut world = World::new();
+        world.set_cell(8, 7, true);
+        world.set_cell(8, 8, true);
+        world.set_cell(8, 9, true);
+        world
+    }
+
+    #[test]
+    fn population_above_triggers_once() {
+        let previous = World::new();
+        let current = blinker();
+        let breakpoint = Breakpoint::PopulationAbove(2);
+
+        assert!(breakpoint.is_triggered(&previous, &current, 1));
+        assert!(!breakpoint.is_triggered(&current, &current, 2));
+    }
+
+    #[test]
+    fn population_below() {
+        let previous = blinker();
+        let current = World::new();
+        let breakpoint = Breakpoint::PopulationBelow(1);
+
+        assert!(breakpoint.is_triggered(&previous, &current, 1));
+        assert!(!breakpoint.is_triggered(&current, &previous, 2));
+    }
+
+    #[test]
+    fn region_changed() {
+        let previous = blinker();
+        let mut current = blinker();
+        current.step();
+
+        let inside = Breakpoint::RegionChanged(Region::new(7, 8, 1, 1));
+        let outside = Breakpoint::RegionChanged(Region::new(0, 0, 4, 4));
+
+        assert!(inside.is_triggered(&previous, &current, 1));
+        assert!(!outside.is_triggered(&previous, &current, 1));
+    }
+
+    #[test]
+    fn cell_alive() {
+        let previous = blinker();
+        let mut current = blinker();
+        current.step();
+
+        assert!(Breakpoint::CellAlive(9, 8).is_triggered(&previous, &current, 1));
+        assert!(!Breakpoint::CellAlive(8, 8).is_triggered(&previous, &current, 1));
+    }
+
+    #[test]
+    fn generation() {
+        let world = World::new();
+        let breakpoint = Breakpoint::Generation(10);
+
+        assert!(!breakpoint.is_triggered(&world, &world, 9));
+        assert!(breakpoint.is_triggered(&world, &world, 10));
+    }
+
+    #[test]
+    fn check_returns_first_triggered() {
+        let mut breakpoints = Breakpoints::new();
+        breakpoints.add(Breakpoint::Generation(5));
+        breakpoints.add(Breakpoint::PopulationAbove(2));
+        breakpoints.add(Breakpoint::CellAlive(8, 8));
+
+        let actual = breakpoints.check(&World::new(), &blinker(), 1);
+        let expected = Some(Breakpoint::PopulationAbove(2));
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn add_and_remove() {
+        let mut breakpoints = Breakpoints::new();
+        for generation in 0..(Breakpoints::CAPACITY as u32) {
+            assert!(breakpoints.add(Breakpoint::Generation(generation)));
+        }
+        assert!(!breakpoints.add(Breakpoint::Generation(100)));
+
+        breakpoints.remove(0);
+
+        assert_eq!(breakpoints.len(), Breakpoints::CAPACITY - 1);
+        assert_eq!(breakpoints.iter().next(), Some(&Breakpoint::Generation(1)));
+    }
+}
@@ -0,0 +1,286 @@
+// Copyright Claudio Mattera 2021.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Compact binary encoding of worlds
+//!
+//! Cells are packed eight per byte in row-major order, least significant bit
+//! first, and the packed bytes are compressed with a PackBits-style
+//! run-length encoding.
+//! A control byte `n` below 128 is followed by `n + 1` literal bytes, a
+//! control byte `n` from 128 is followed by a single byte repeated `n - 125`
+//! times.
+//!
+//! The encoding starts with a byte telling whether the rest is compressed or
+//! just packed, whichever is shorter, so an encoded world never takes more
+//! than [`MAX_ENCODED_SIZE`] bytes.
+
+use super::World;
+
+/// Number of bytes of a world with cells packed eight per byte
+pub(super) const PACKED_SIZE: usize = (World::WIDTH * World::HEIGHT - 1) / 8 + 1;
+
+/// Maximal number of bytes of an encoded world
+pub const MAX_ENCODED_SIZE: usize = 1 + PACKED_SIZE;
+
+const PACKED: u8 = 0;
+const COMPRESSED: u8 = 1;
+
+const MAX_LITERALS: usize = 128;
+const MIN_REPEATS: usize = 3;
+const MAX_REPEATS: usize = 130;
+
+/// Encode a world into a buffer, returning the number of bytes used
+pub fn encode_world(world: &World, buffer: &mut [u8; MAX_ENCODED_SIZE]) -> usize {
+    let mut packed = [0; PACKED_SIZE];
+    pack_world(world, &mut packed);
+
+    buffer[0] = COMPRESSED;
+    match compress(&packed, &mut buffer[1..]) {
+        Some(size) => 1 + size,
+        None => {
+            buffer[0] = PACKED;
+            buffer[1..].copy_from_slice(&packed);
+            MAX_ENCODED_SIZE
+        }
+    }
+}
+
+/// Decode a world, failing unless the data is exactly one encoded world
+pub fn decode_world(data: &[u8]) -> Option<World> {
+    let mut world = World::new();
+    decode_world_into(data, &mut world)?;
+    Some(world)
+}
+
+/// Decode a world in place, leaving it unchanged unless the data is exactly
+/// one encoded world
+pub(super) fn decode_world_into(data: &[u8], world: &mut World) -> Option<()> {
+    let (mode, data) = data.split_first()?;
+    match *mode {
+        PACKED if data.len() == PACKED_SIZE => unpack_world(data, world),
+        COMPRESSED => {
+            let mut packed = [0; PACKED_SIZE];
+            decompress(data, &mut packed)?;
+            unpack_world(&packed, world);
+        }
+        _ => return None,
+    }
+    Some(())
+}
+
+/// Pack the cells of a world eight per byte, least significant bit first
+pub(super) fn pack_world(world: &World, bytes: &mut [u8; PACKED_SIZE]) {
+    for byte in bytes.iter_mut() {
+        *byte = 0;
+    }
+    for y in 0..World::HEIGHT {
+        for x in 0..World::WIDTH {
+            if world.get_cell(x as i16, y as i16) {
+                let i = y * World::WIDTH + x;
+                bytes[i / 8] |= 1 << (i % 8);
+            }
+        }
+    }
+}
+
+pub(super) fn unpack_world(bytes: &[u8], world: &mut World) {
+    for y in 0..World::HEIGHT {
+        for x in 0..World::WIDTH {
+            let i = y * World::WIDTH + x;
+            world.set_cell(x as i16, y as i16, bytes[i / 8] & (1 << (i % 8)) != 0);
+        }
+    }
+}
+
+/// Compress bytes into a buffer, failing if the buffer is too short
+fn compress(input: &[u8], output: &mut [u8]) -> Option<usize> {
+    let mut size = 0;
+    let mut literals = 0;
+    let mut i = 0;
+    while i < input.len() {
+        let repeats = input[i..]
+            .iter()
+            .take(MAX_REPEATS)
+            .take_while(|byte| **byte == input[i])
+            .count();
+
+        if repeats >= MIN_REPEATS {
+            size = flush_literals(&input[i - literals..i], output, size)?;
+            literals = 0;
+            *output.get_mut(size)? = (repeats + 125) as u8;
+            *output.get_mut(size + 1)? = input[i];
+            size += 2;
+            i += repeats;
+        } else {
+            literals += 1;
+            i += 1;
+            if literals == MAX_LITERALS {
+                size = flush_literals(&input[i - literals..i], output, size)?;
+                literals = 0;
+            }
+        }
+    }
+    flush_literals(&input[i - literals..i], output, size)
+}
+
+fn flush_literals(literals: &[u8], output: &mut [u8], size: usize) -> Option<usize> {
+    if literals.is_empty() {
+        return Some(size);
+    }
+    let end = size + 1 + literals.len();
+    if end > output.len() {
+        return None;
+    }
+    output[size] = (literals.len() - 1) as u8;
+    output[size + 1..end].copy_from_slice(literals);
+    Some(end)
+}
+
+/// Decompress bytes, failing unless they fill the output exactly
+fn decompress(input: &[u8], output: &mut [u8]) -> Option<()> {
+    let mut size = 0;
+    let mut i = 0;
+    while i < input.len() {
+        let control = input[i] as usize;
+        if control < MAX_LITERALS {
+            let literals = input.get(i + 1..i + 2 + control)?;
+            output
+                .get_mut(size..size + literals.len())?
+                .copy_from_slice(literals);
+            size += literals.len();
+            i += 2 + control;
+        } else {
+            let byte = *input.get(i + 1)?;
+            let repeats = control - 125;
+            for target in output.get_mut(size..size + repeats)? {
+                *target = byte;
+            }
+            size += repeats;
+            i += 2;
+        }
+    }
+    if size == output.len() {
+        Some(())
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::analysis::{soup, Random};
+
+    fn round_trip(world: &World) -> usize {
+        let mut buffer = [0; MAX_ENCODED_SIZE];
+
+        let size = encode_world(world, &mut buffer);
+        let actual = decode_world(&buffer[..size]);
+
+        assert_eq!(actual.as_ref(), Some(world));
+        size
+    }
+
+    #[test]
+    fn empty_world() {
+        let size = round_trip(&World::new());
+
+        assert_eq!(size, 3);
+    }
+
+    #[test]
+    fn full_world() {
+        let mut world = World::new();
+        for y in 0..World::HEIGHT as i16 {
+            for x in 0..World::WIDTH as i16 {
+                world.set_cell(x, y, true);
+            }
+        }
+
+        let size = round_trip(&world);
+
+        assert_eq!(size, 5);
+    }
+
+    #[test]
+    fn glider() {
+        let mut world = World::new();
+        world.set_cell(8, 6, true);
+        world.set_cell(9, 7, true);
+        world.set_cell(7, 8, true);
+        world.set_cell(8, 8, true);
+        world.set_cell(9, 8, true);
+
+        let size = round_trip(&world);
+
+        assert!(size < 24);
+    }
+
+    #[test]
+    fn checkerboard() {
+        let mut world = World::new();
+        for y in 0..World::HEIGHT as i16 {
+            for x in 0..World::WIDTH as i16 {
+                world.set_cell(x, y, (x + y) % 2 == 0);
+            }
+        }
+
+        let size = round_trip(&world);
+
+        assert!(size <= MAX_ENCODED_SIZE);
+    }
+
+    #[test]
+    fn random_worlds() {
+        let mut random = Random::new(42);
+        for _ in 0..200 {
+            let mut world = World::new();
+            for y in 0..World::HEIGHT as i16 {
+                for x in 0..World::WIDTH as i16 {
+                    world.set_cell(x, y, random.next_bool());
+                }
+            }
+
+            let size = round_trip(&world);
+
+            assert!(size <= MAX_ENCODED_SIZE);
+        }
+    }
+
+    #[test]
+    fn soups() {
+        for seed in 0..50 {
+            round_trip(&soup(seed));
+        }
+    }
+
+    #[test]
+    fn literals_and_runs() {
+        let mut input = [0; PACKED_SIZE];
+        for (i, byte) in input.iter_mut().enumerate() {
+            *byte = if i < 40 { i as u8 } else { 0xaa };
+        }
+        let mut compressed = [0; PACKED_SIZE];
+        let size = compress(&input, &mut compressed).unwrap();
+
+        let mut actual = [0; PACKED_SIZE];
+        decompress(&compressed[..size], &mut actual).unwrap();
+
+        assert_eq!(size, 1 + 40 + 2);
+        assert_eq!(&actual[..], &input[..]);
+    }
+
+    #[test]
+    fn invalid_data() {
+        assert_eq!(decode_world(&[]), None);
+        assert_eq!(decode_world(&[2, 0]), None);
+        assert_eq!(decode_world(&[PACKED, 0, 0]), None);
+        assert_eq!(decode_world(&[COMPRESSED, 255, 0]), None);
+        assert_eq!(decode_world(&[COMPRESSED, 255, 0, 255, 0]), None);
+        assert_eq!(decode_world(&[COMPRESSED, 5, 0]), None);
+    }
+}
@@ -6,73 +6,294 @@
 
 //! Game main loop
 
+use crate::analysis::{ActivityMap, Cycle, CycleDetector, Direction, ObjectCounter, Statistics};
+use crate::format::embed;
 use crate::graphics::{
-    draw_horizontal_line, draw_rect, draw_vertical_line, set_drawing_colors, Palette,
+    draw_horizontal_line, draw_rect, draw_text, draw_vertical_line, set_drawing_colors,
 };
 use crate::input::Mouse;
-use crate::interface::{draw_frame, draw_title, Button, CheckBox, Label, SpinBox};
+use crate::interface::{draw_frame, draw_title, Button, CheckBox, Label, SpinBox, TextBuffer};
+use crate::storage::Disk;
 use crate::time::Ticker;
 
-use super::World;
+use super::{
+    read_slot, Breakpoint, Breakpoints, Pattern, Region, Rule, Save, Settings, Stamp, Topology,
+    World, SETTINGS_OFFSET,
+};
+
+mod breakpoints;
+mod menu;
+mod patterns;
+mod rules;
+mod selection;
+mod settings;
+mod slots;
+mod stamp;
+
+/// A screen of the game
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Screen {
+    World,
+    Menu,
+    Breakpoints,
+    Rules,
+    Slots,
+    Settings,
+    Patterns,
+}
 
+// The stack of the cartridge only has room for a couple of worlds, so worlds,
+// saves and the disk image are kept in statics and updated in place
+static mut SCREEN: Screen = Screen::World;
 static mut WORLD: World = World::new();
+/// Previous generation of the world, for breakpoints and analyses
+static mut PREVIOUS: World = World::new();
+/// Next generation of the world, for the preview
+static mut NEXT: World = World::new();
+static mut GENERATION: u32 = 0;
+static mut BREAKPOINTS: Breakpoints = Breakpoints::new();
+static mut FIRED_BREAKPOINT: Option<Breakpoint> = None;
+static mut WATCH_START: Option<(i16, i16)> = None;
+static mut ACTIVITY: ActivityMap = ActivityMap::new();
+static mut STATISTICS: Option<Statistics> = None;
 static mut PAUSED: bool = false;
-static mut SPEED: u8 = 1;
+static mut CYCLE_DETECTOR: CycleDetector = CycleDetector::new();
+static mut CYCLE: Option<Cycle> = None;
+static mut OBJECT_COUNTER: ObjectCounter = ObjectCounter::new();
+static mut OBJECT_COUNT: usize = 0;
+static mut STAMP: Option<Stamp> = None;
+/// Content of the disk, shared by everything reading or writing it
+static mut DISK_IMAGE: [u8; Disk::SIZE] = [0; Disk::SIZE];
+/// Save read from or written to the disk
+static mut SAVE: Save = Save::EMPTY;
+static mut SETTINGS: Settings = Settings::DEFAULT;
+static mut STORED_SETTINGS: Settings = Settings::DEFAULT;
 static mut BUTTON_COOLDOWN: u8 = 0;
 
 const MAX_BUTTON_COOLDOWN: u8 = 6;
 
+/// Pattern placed in the world when there is no saved game
+const GLIDER: Pattern = embed::rle("bo$2bo$3o!");
+
 #[no_mangle]
 fn start() {
-    Palette::IceCream.set();
-
     let world = unsafe { &mut WORLD };
+    let settings = unsafe { &mut SETTINGS };
+    let stored_settings = unsafe { &mut STORED_SETTINGS };
+    let image = unsafe { &mut DISK_IMAGE };
+    let save = unsafe { &mut SAVE };
+
+    Disk.read(image);
+
+    *settings = Settings::read(image).unwrap_or_default();
+    *stored_settings = *settings;
+    settings.palette.set();
+
+    // The world in the first slot is restored when the game starts
+    if read_slot(image, 0, save).is_ok() {
+        world.replace(&save.world);
+        unsafe { GENERATION = save.generation };
+        unsafe { PAUSED = save.paused };
+        return;
+    }
 
-    world.set_cell(8, 6, true);
-    world.set_cell(9, 7, true);
-    world.set_cell(7, 8, true);
-    world.set_cell(8, 8, true);
-    world.set_cell(9, 8, true);
+    world.place_pattern(&GLIDER, 7, 6);
 }
 
 #[no_mangle]
 fn update() {
+    let screen = unsafe { &mut SCREEN };
     let world = unsafe { &mut WORLD };
+    let previous = unsafe { &mut PREVIOUS };
+    let next = unsafe { &mut NEXT };
+    let settings = unsafe { &mut SETTINGS };
+    let stored_settings = unsafe { &mut STORED_SETTINGS };
+    let generation = unsafe { &mut GENERATION };
+    let breakpoints = unsafe { &mut BREAKPOINTS };
+    let fired_breakpoint = unsafe { &mut FIRED_BREAKPOINT };
+    let watch_start = unsafe { &mut WATCH_START };
+    let activity = unsafe { &mut ACTIVITY };
+    let statistics = unsafe { &mut STATISTICS };
     let paused = unsafe { &mut PAUSED };
-    let speed = unsafe { &mut SPEED };
+    let cycle_detector = unsafe { &mut CYCLE_DETECTOR };
+    let cycle = unsafe { &mut CYCLE };
+    let object_counter = unsafe { &mut OBJECT_COUNTER };
+    let object_count = unsafe { &mut OBJECT_COUNT };
+    let stamp = unsafe { &mut STAMP };
+    let disk_image = unsafe { &mut DISK_IMAGE };
+    let save = unsafe { &mut SAVE };
     let button_cooldown = unsafe { &mut BUTTON_COOLDOWN };
 
+    if settings != stored_settings {
+        store_settings(settings);
+        if settings.palette != stored_settings.palette {
+            settings.palette.set();
+        }
+        *stored_settings = *settings;
+    }
+
+    let Settings {
+        palette: _,
+        speed,
+        rule,
+        topology,
+        heat_map,
+        statistics_panel,
+        preview,
+        auto_pause,
+    } = settings;
+
     let offset = (0, 30);
 
-    draw_with_mouse(&Mouse, offset, world);
+    if *screen == Screen::World {
+        selection::toggle();
+        let changed = if stamp.is_some() {
+            stamp::operate(stamp, world, offset)
+        } else if selection::is_active() {
+            selection::operate(world, stamp, offset)
+        } else {
+            draw_with_mouse(&Mouse, offset, world)
+        };
+        if changed {
+            cycle_detector.reset();
+            *cycle = None;
+            *statistics = None;
+        }
+        watch_with_mouse(&Mouse, offset, watch_start, breakpoints);
+    }
 
     let period = 60 / *speed;
     if !*paused && Ticker.get() % period == 0 {
-        propagate_life(world);
+        previous.replace(world);
+        propagate_life(world, *rule, *topology);
+        *generation = generation.wrapping_add(1);
+        activity.record(previous, world);
+        if *statistics_panel {
+            *statistics = Some(Statistics::compute(previous, world));
+        }
+
+        *fired_breakpoint = breakpoints.check(previous, world, *generation);
+        if fired_breakpoint.is_some() {
+            *paused = true;
+        }
+
+        let previous_cycle = *cycle;
+        *cycle = cycle_detector.observe(world);
+        if previous_cycle.is_none() && cycle.is_some() {
+            *object_count = object_counter.count(world);
+            if *auto_pause {
+                *paused = true;
+            }
+        }
     }
 
-    let controls_y = 118;
-    let (mut paused_checkbox, label, minus_button, spinbox, plus_button) =
-        create_user_controls(controls_y, *paused, *speed);
+    match *screen {
+        Screen::World => {
+            let controls_y = 118;
+            let (
+                mut paused_checkbox,
+                mut auto_pause_checkbox,
+                menu_button,
+                label,
+                minus_button,
+                spinbox,
+                plus_button,
+            ) = create_user_controls(controls_y, *paused, *auto_pause, *speed);
+
+            operate_user_controls(
+                &mut paused_checkbox,
+                &mut auto_pause_checkbox,
+                &menu_button,
+                &minus_button,
+                &plus_button,
+                screen,
+                paused,
+                auto_pause,
+                speed,
+                button_cooldown,
+            );
+
+            if *statistics_panel && statistics.is_none() {
+                *statistics = Some(Statistics::of(world));
+            }
 
-    operate_user_controls(
-        &mut paused_checkbox,
-        &minus_button,
-        &plus_button,
-        paused,
-        speed,
-        button_cooldown,
-    );
+            if *preview {
+                next_generation(world, next, *rule, *topology);
+            }
 
-    draw(
-        offset,
-        world,
-        &paused_checkbox,
-        &label,
-        &minus_button,
-        &spinbox,
-        &plus_button,
-    );
+            draw(
+                offset,
+                world,
+                statistics.as_ref().filter(|_| *statistics_panel),
+                stamp.as_ref(),
+                heat_map.then(|| &*activity),
+                preview.then(|| &*next),
+                breakpoints,
+                *watch_start,
+                *fired_breakpoint,
+                *cycle,
+                *object_count,
+                &paused_checkbox,
+                &auto_pause_checkbox,
+                &menu_button,
+                &label,
+                &minus_button,
+                &spinbox,
+                &plus_button,
+            );
+        }
+        Screen::Menu => menu::update(screen, heat_map, statistics_panel, preview),
+        Screen::Slots => {
+            save.world.replace(world);
+            save.rule = *rule;
+            save.generation = *generation;
+            save.speed = *speed;
+            save.paused = *paused;
+            save.auto_pause = *auto_pause;
+            save.heat_map = *heat_map;
+            save.statistics_panel = *statistics_panel;
+            save.preview = *preview;
+            if slots::update(screen, disk_image, save) {
+                restore(
+                    save,
+                    world,
+                    rule,
+                    generation,
+                    speed,
+                    paused,
+                    auto_pause,
+                    heat_map,
+                    statistics_panel,
+                    preview,
+                );
+                activity.reset();
+                cycle_detector.reset();
+                *cycle = None;
+                *statistics = None;
+                *fired_breakpoint = None;
+            }
+        }
+        Screen::Breakpoints => {
+            breakpoints::update(screen, breakpoints, *generation, button_cooldown)
+        }
+        Screen::Rules => {
+            if rules::update(screen, rule) {
+                cycle_detector.reset();
+                *cycle = None;
+            }
+        }
+        Screen::Patterns => {
+            if let Some(pattern) = patterns::update(screen) {
+                *stamp = Some(Stamp::new(pattern));
+            }
+        }
+        Screen::Settings => {
+            if settings::update(screen, settings) {
+                cycle_detector.reset();
+                *cycle = None;
+            }
+        }
+    }
 
     Mouse.update();
     Ticker.update();
@@ -85,59 +306,138 @@ fn draw_mouse_pointer() {
     draw_horizontal_line(x as i32 - 1, y as i32, 3);
 }
 
-fn draw_with_mouse(mouse: &Mouse, offset: (i32, i32), world: &mut World) {
+/// Find the cell of the world under the mouse
+fn world_cell(mouse: &Mouse, offset: (i32, i32)) -> Option<(i16, i16)> {
+    let (x, y) = mouse.coordinates();
+    let x = (x - offset.0 as i16) / 4 - 1;
+    let y = (y - offset.1 as i16) / 4 - 1;
+    if x >= 0 && x < (World::WIDTH as i16) && y >= 0 && y < (World::HEIGHT as i16) {
+        Some((x, y))
+    } else {
+        None
+    }
+}
+
+/// Draw or erase cells with the mouse, returning whether the world changed
+fn draw_with_mouse(mouse: &Mouse, offset: (i32, i32), world: &mut World) -> bool {
     if mouse.left_pressed() || mouse.right_pressed() {
-        let (x, y) = mouse.coordinates();
-        let x = (x - offset.0 as i16) / 4 - 1;
-        let y = (y - offset.1 as i16) / 4 - 1;
-        if x >= 0 && x < (World::WIDTH as i16) && y >= 0 && y < (World::HEIGHT as i16) {
-            if mouse.left_pressed() {
-                world.set_cell(x, y, true);
-            } else {
-                world.set_cell(x, y, false);
+        if let Some((x, y)) = world_cell(mouse, offset) {
+            let value = mouse.left_pressed();
+            if world.get_cell(x, y) != value {
+                world.set_cell(x, y, value);
+                return true;
             }
         }
     }
+    false
 }
 
-fn propagate_life(world: &mut World) {
-    let mut new_world = World::new();
-
-    for x in 0..(World::WIDTH as i16) {
-        for y in 0..(World::HEIGHT as i16) {
-            let alive = world.get_cell(x, y);
-            let count = world.count_live_neighbours(x, y);
-
-            if alive {
-                new_world.set_cell(x, y, (2..4).contains(&count));
+/// Watch cells by dragging with the middle button
+///
+/// Dragging over a single cell watches it becoming alive, dragging over a
+/// larger region watches any change in it.
+fn watch_with_mouse(
+    mouse: &Mouse,
+    offset: (i32, i32),
+    watch_start: &mut Option<(i16, i16)>,
+    breakpoints: &mut Breakpoints,
+) {
+    if mouse.middle_pressed() {
+        if watch_start.is_none() {
+            *watch_start = world_cell(mouse, offset);
+        }
+    } else if let Some(start) = watch_start.take() {
+        if let Some(end) = world_cell(mouse, offset) {
+            let region = Region::from_corners(start, end);
+            if region.width == 1 && region.height == 1 {
+                breakpoints.add(Breakpoint::CellAlive(region.x, region.y));
             } else {
-                new_world.set_cell(x, y, count == 3);
+                breakpoints.add(Breakpoint::RegionChanged(region));
             }
         }
     }
+}
+
+/// Restore the state of the game from a save
+#[allow(clippy::too_many_arguments)]
+fn restore(
+    save: &Save,
+    world: &mut World,
+    rule: &mut Rule,
+    generation: &mut u32,
+    speed: &mut u8,
+    paused: &mut bool,
+    auto_pause: &mut bool,
+    heat_map: &mut bool,
+    statistics_panel: &mut bool,
+    preview: &mut bool,
+) {
+    world.replace(&save.world);
+    *rule = save.rule;
+    *generation = save.generation;
+    *speed = save.speed.clamp(1, 6);
+    *paused = save.paused;
+    *auto_pause = save.auto_pause;
+    *heat_map = save.heat_map;
+    *statistics_panel = save.statistics_panel;
+    *preview = save.preview;
+}
+
+/// Write the settings to the disk, after the save slots
+fn store_settings(settings: &Settings) {
+    let image = unsafe { &mut DISK_IMAGE };
+    let mut buffer = [0; Settings::MAX_SIZE];
+    let size = settings.encode(&mut buffer);
+    Disk.write_at(image, SETTINGS_OFFSET, &buffer[..size]);
+}
 
-    world.replace(&new_world);
+fn propagate_life(world: &mut World, rule: Rule, topology: Topology) {
+    world.step_in(rule, topology);
+}
+
+/// Compute the next generation into another world without modifying the world
+fn next_generation(world: &World, next: &mut World, rule: Rule, topology: Topology) {
+    next.replace(world);
+    propagate_life(next, rule, topology);
 }
 
 fn create_user_controls(
     controls_y: i32,
     paused: bool,
+    auto_pause: bool,
     speed: u8,
-) -> (CheckBox, Label, Button, SpinBox, Button) {
+) -> (CheckBox, CheckBox, Button, Label, Button, SpinBox, Button) {
     let paused_button_y = 15;
     let row_y = 0;
 
-    let checkbox_width = 4 + 8 * 10;
-    let checkbox_x = (160 - checkbox_width as i32) / 2;
+    let checkbox_width = 4 + 8 * 5;
+    let checkboxes_x = (160 - 3 * checkbox_width as i32 - 2 * 6) / 2;
     let mut paused_checkbox = CheckBox::new(
         "Pause",
-        checkbox_x,
+        checkboxes_x,
         controls_y + paused_button_y,
         checkbox_width,
         4 + 8,
     );
     paused_checkbox.set_pressed(paused);
 
+    let mut auto_pause_checkbox = CheckBox::new(
+        "Auto",
+        checkboxes_x + checkbox_width as i32 + 6,
+        controls_y + paused_button_y,
+        checkbox_width,
+        4 + 8,
+    );
+    auto_pause_checkbox.set_pressed(auto_pause);
+
+    let menu_button = Button::new(
+        "Menu",
+        checkboxes_x + 2 * (checkbox_width as i32 + 6),
+        controls_y + paused_button_y,
+        checkbox_width,
+        4 + 8,
+    );
+
     let row_width = (4 + 8 * 7) + (4 + 8) + (4 + 8) + (4 + 8);
     let row_x = (160 - row_width as i32) / 2;
 
@@ -148,21 +448,39 @@ fn create_user_controls(
 
     let label = Label::new("Speed:", label_x, controls_y + row_y, 4 + 8 * 7, 4 + 8);
     let minus_button = Button::new("-", minus_button_x, controls_y + row_y, 4 + 8, 4 + 8);
-    let spinbox = SpinBox::new(speed, spinbox_x, controls_y + row_y, 4 + 8, 4 + 8);
+    let spinbox = SpinBox::new(speed as u32, spinbox_x, controls_y + row_y, 4 + 8, 4 + 8);
     let plus_button = Button::new("+", plus_button_x, controls_y + row_y, 4 + 8, 4 + 8);
 
-    (paused_checkbox, label, minus_button, spinbox, plus_button)
+    (
+        paused_checkbox,
+        auto_pause_checkbox,
+        menu_button,
+        label,
+        minus_button,
+        spinbox,
+        plus_button,
+    )
 }
 
+#[allow(clippy::too_many_arguments)]
 fn operate_user_controls(
     paused_checkbox: &mut CheckBox,
+    auto_pause_checkbox: &mut CheckBox,
+    menu_button: &Button,
     minus_button: &Button,
     plus_button: &Button,
+    screen: &mut Screen,
     paused: &mut bool,
+    auto_pause: &mut bool,
     speed: &mut u8,
     button_cooldown: &mut u8,
 ) {
     paused_checkbox.toggle(&Mouse);
+    auto_pause_checkbox.toggle(&Mouse);
+
+    if menu_button.is_released(&Mouse) {
+        *screen = Screen::Menu;
+    }
 
     if *button_cooldown == 0 && plus_button.is_clicked(&Mouse) && *speed < 6 {
         *speed += 1;
@@ -175,34 +493,163 @@ fn operate_user_controls(
     }
 
     *paused = paused_checkbox.is_pressed();
+    *auto_pause = auto_pause_checkbox.is_pressed();
 
     if *button_cooldown > 0 {
         *button_cooldown -= 1;
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn draw(
     offset: (i32, i32),
     world: &World,
+    statistics: Option<&Statistics>,
+    stamp: Option<&Stamp>,
+    activity: Option<&ActivityMap>,
+    next: Option<&World>,
+    breakpoints: &Breakpoints,
+    watch_start: Option<(i16, i16)>,
+    fired_breakpoint: Option<Breakpoint>,
+    cycle: Option<Cycle>,
+    object_count: usize,
     paused_checkbox: &CheckBox,
+    auto_pause_checkbox: &CheckBox,
+    menu_button: &Button,
     label: &Label,
     minus_button: &Button,
     spinbox: &SpinBox,
     plus_button: &Button,
 ) {
-    draw_title();
+    let toolbar = stamp.is_some() || selection::is_active();
+    match statistics {
+        _ if toolbar => {}
+        Some(statistics) => draw_statistics(statistics),
+        None => draw_title(),
+    }
     draw_frame(offset);
-    draw_world(world, offset);
+    match activity {
+        Some(activity) => draw_heat_map(activity, offset),
+        None => draw_world(world, offset),
+    }
+    if let Some(next) = next {
+        draw_preview(world, next, offset);
+    }
+    draw_watches(breakpoints, watch_start, offset);
+    selection::draw(offset, stamp.is_some());
+    if let Some(stamp) = stamp {
+        stamp::draw(stamp, offset);
+    }
 
     paused_checkbox.draw(&Mouse);
+    auto_pause_checkbox.draw(&Mouse);
+    menu_button.draw(&Mouse);
     label.draw();
     minus_button.draw(&Mouse);
     spinbox.draw();
     plus_button.draw(&Mouse);
 
+    draw_status(fired_breakpoint, cycle, object_count);
+
     draw_mouse_pointer();
 }
 
+fn draw_status(fired_breakpoint: Option<Breakpoint>, cycle: Option<Cycle>, object_count: usize) {
+    let mut text = TextBuffer::<20>::new();
+    if let Some(breakpoint) = fired_breakpoint {
+        text.push_str("Break ");
+        breakpoints::describe(&breakpoint, &mut text);
+    } else {
+        match cycle {
+            None => return,
+            Some(Cycle::Extinct) => text.push_str("Extinct"),
+            Some(Cycle::Static) => text.push_str("Static"),
+            Some(Cycle::Periodic(period)) => text.push_str("Period ").push_number(period as u32),
+            Some(Cycle::Moving(velocity)) => {
+                let (numerator, denominator) = velocity.speed();
+                if numerator != 1 {
+                    text.push_number(numerator as u32);
+                }
+                text.push_str("c/").push_number(denominator as u32);
+                match velocity.direction() {
+                    Direction::Orthogonal => text.push_str(" orth"),
+                    Direction::Diagonal => text.push_str(" diag"),
+                    Direction::Oblique => text.push_str(" obl"),
+                }
+            }
+        };
+        if object_count > 0 {
+            text.push_str(", ")
+                .push_number(object_count as u32)
+                .push_str(" obj");
+        }
+    }
+
+    draw_centered_text(text.as_str(), 148);
+}
+
+/// Draw a summary of the statistics in place of the title
+///
+/// The first line shows population, births and deaths, the second one shows
+/// the spatial entropy at each block size and the largest symmetry measure,
+/// as percentages.
+fn draw_statistics(statistics: &Statistics) {
+    let mut text = TextBuffer::<20>::new();
+    text.push_str("Pop ")
+        .push_number(statistics.population as u32)
+        .push_str(" +")
+        .push_number(statistics.births as u32)
+        .push_str(" -")
+        .push_number(statistics.deaths as u32);
+    draw_centered_text(text.as_str(), 3);
+
+    let mut text = TextBuffer::<20>::new();
+    text.push_str("H");
+    for entropy in statistics.entropy.iter() {
+        text.push_str(" ").push_number(percentage(*entropy));
+    }
+    text.push_str(" S ")
+        .push_number(percentage(statistics.symmetry.max()));
+    draw_centered_text(text.as_str(), 14);
+}
+
+fn percentage(fraction: f32) -> u32 {
+    (fraction * 100.0 + 0.5) as u32
+}
+
+fn draw_centered_text(string: &str, y: i32) {
+    let x = (160 - 8 * string.len() as i32) / 2;
+    set_drawing_colors(0x03);
+    draw_text(string, x, y);
+}
+
+/// Outline the cells and regions watched by breakpoints
+fn draw_watches(breakpoints: &Breakpoints, watch_start: Option<(i16, i16)>, offset: (i32, i32)) {
+    set_drawing_colors(0x40);
+    for breakpoint in breakpoints.iter() {
+        match *breakpoint {
+            Breakpoint::CellAlive(x, y) => draw_region_outline(Region::new(x, y, 1, 1), offset),
+            Breakpoint::RegionChanged(region) => draw_region_outline(region, offset),
+            _ => {}
+        }
+    }
+
+    if let Some(start) = watch_start {
+        if let Some(end) = world_cell(&Mouse, offset) {
+            draw_region_outline(Region::from_corners(start, end), offset);
+        }
+    }
+}
+
+fn draw_region_outline(region: Region, offset: (i32, i32)) {
+    draw_rect(
+        offset.0 + 4 + (region.x * 4) as i32,
+        offset.1 + 4 + (region.y * 4) as i32,
+        region.width as u32 * 4,
+        region.height as u32 * 4,
+    );
+}
+
 fn draw_world(world: &World, offset: (i32, i32)) {
     set_drawing_colors(2);
     for x in 0..(World::WIDTH as i16) {
@@ -218,3 +665,35 @@ fn draw_world(world: &World, offset: (i32, i32)) {
         }
     }
 }
+
+/// Outline the cells that will be born or die in the next generation
+fn draw_preview(world: &World, next: &World, offset: (i32, i32)) {
+    for x in 0..(World::WIDTH as i16) {
+        for y in 0..(World::HEIGHT as i16) {
+            match (world.get_cell(x, y), next.get_cell(x, y)) {
+                (false, true) => set_drawing_colors(0x30),
+                (true, false) => set_drawing_colors(0x40),
+                _ => continue,
+            }
+            draw_region_outline(Region::new(x, y, 1, 1), offset);
+        }
+    }
+}
+
+/// Draw how often cells changed, using darker colours for more active cells
+fn draw_heat_map(activity: &ActivityMap, offset: (i32, i32)) {
+    for x in 0..(World::WIDTH as i16) {
+        for y in 0..(World::HEIGHT as i16) {
+            let level = activity.level(x, y);
+            if level > 0 {
+                set_drawing_colors(level as u16 + 1);
+                draw_rect(
+                    offset.0 + 4 + (x * 4) as i32,
+                    offset.1 + 4 + (y * 4) as i32,
+                    4,
+                    4,
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,148 @@
+// Copyright Claudio Mattera 2021.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Breakpoints screen
+
+use crate::game::{Breakpoint, Breakpoints};
+use crate::graphics::{draw_text, set_drawing_colors};
+use crate::input::Mouse;
+use crate::interface::{draw_title, Button, SpinBox, TextBuffer};
+
+use super::{draw_centered_text, draw_mouse_pointer, Screen, MAX_BUTTON_COOLDOWN};
+
+/// Kinds of breakpoints that can be created from this screen
+///
+/// Breakpoints on cells and regions are created by dragging with the middle
+/// button on the world.
+const KINDS: [&str; 3] = ["Pop>", "Pop<", "Gen="];
+
+static mut KIND: usize = 0;
+static mut VALUE: u32 = 0;
+
+pub(super) fn update(
+    screen: &mut Screen,
+    breakpoints: &mut Breakpoints,
+    generation: u32,
+    button_cooldown: &mut u8,
+) {
+    let kind = unsafe { &mut KIND };
+    let value = unsafe { &mut VALUE };
+
+    draw_title();
+
+    let mut removed = None;
+    for (i, breakpoint) in breakpoints.iter().enumerate() {
+        let y = 32 + 13 * i as i32;
+
+        let mut text = TextBuffer::<20>::new();
+        describe(breakpoint, &mut text);
+        set_drawing_colors(0x03);
+        draw_text(text.as_str(), 8, y + 2);
+
+        let remove_button = Button::new("x", 140, y, 4 + 8, 4 + 8);
+        if remove_button.is_released(&Mouse) {
+            removed = Some(i);
+        }
+        remove_button.draw(&Mouse);
+    }
+    if let Some(i) = removed {
+        breakpoints.remove(i);
+    }
+
+    if breakpoints.is_empty() {
+        draw_centered_text("No breakpoints", 40);
+        draw_centered_text("Middle-drag on", 60);
+        draw_centered_text("the world to", 70);
+        draw_centered_text("watch cells", 80);
+    }
+
+    let row_y = 118;
+    let row_width = (4 + 8 * 4) + (4 + 8) + (4 + 8 * 4) + (4 + 8) + (4 + 8 * 3) + 4 * 4;
+    let row_x = (160 - row_width) / 2;
+    let kind_button = Button::new(KINDS[*kind], row_x, row_y, 4 + 8 * 4, 4 + 8);
+    let minus_button = Button::new("-", row_x + 40, row_y, 4 + 8, 4 + 8);
+    let spinbox = SpinBox::new(*value, row_x + 56, row_y, 4 + 8 * 4, 4 + 8);
+    let plus_button = Button::new("+", row_x + 96, row_y, 4 + 8, 4 + 8);
+    let add_button = Button::new("Add", row_x + 112, row_y, 4 + 8 * 3, 4 + 8);
+
+    let bottom_y = 133;
+    let clear_button = Button::new("Clear", 24, bottom_y, 4 + 8 * 6, 4 + 8);
+    let back_button = Button::new("Back", 84, bottom_y, 4 + 8 * 6, 4 + 8);
+
+    let step = if *kind == 2 { 10 } else { 5 };
+    if kind_button.is_released(&Mouse) {
+        *kind = (*kind + 1) % KINDS.len();
+    }
+    if *button_cooldown == 0 && plus_button.is_clicked(&Mouse) {
+        *value = value.saturating_add(step);
+        *button_cooldown = MAX_BUTTON_COOLDOWN;
+    }
+    if *button_cooldown == 0 && minus_button.is_clicked(&Mouse) {
+        *value = value.saturating_sub(step);
+        *button_cooldown = MAX_BUTTON_COOLDOWN;
+    }
+    if add_button.is_released(&Mouse) {
+        breakpoints.add(match *kind {
+            0 => Breakpoint::PopulationAbove(*value as usize),
+            1 => Breakpoint::PopulationBelow(*value as usize),
+            _ => Breakpoint::Generation(*value),
+        });
+    }
+    if clear_button.is_released(&Mouse) {
+        breakpoints.clear();
+    }
+    if back_button.is_released(&Mouse) {
+        *screen = Screen::World;
+    }
+    if *button_cooldown > 0 {
+        *button_cooldown -= 1;
+    }
+
+    kind_button.draw(&Mouse);
+    minus_button.draw(&Mouse);
+    spinbox.draw();
+    plus_button.draw(&Mouse);
+    add_button.draw(&Mouse);
+    clear_button.draw(&Mouse);
+    back_button.draw(&Mouse);
+
+    let mut text = TextBuffer::<20>::new();
+    text.push_str("Generation ").push_number(generation);
+    draw_centered_text(text.as_str(), 148);
+
+    draw_mouse_pointer();
+}
+
+/// Describe a breakpoint in a short text
+pub(super) fn describe<const N: usize>(breakpoint: &Breakpoint, text: &mut TextBuffer<N>) {
+    match *breakpoint {
+        Breakpoint::PopulationAbove(threshold) => {
+            text.push_str("Pop > ").push_number(threshold as u32);
+        }
+        Breakpoint::PopulationBelow(threshold) => {
+            text.push_str("Pop < ").push_number(threshold as u32);
+        }
+        Breakpoint::RegionChanged(region) => {
+            text.push_str("Reg ")
+                .push_number(region.x as u32)
+                .push_str(",")
+                .push_number(region.y as u32)
+                .push_str(" ")
+                .push_number(region.width as u32)
+                .push_str("x")
+                .push_number(region.height as u32);
+        }
+        Breakpoint::CellAlive(x, y) => {
+            text.push_str("Cell ")
+                .push_number(x as u32)
+                .push_str(",")
+                .push_number(y as u32);
+        }
+        Breakpoint::Generation(generation) => {
+            text.push_str("Gen ").push_number(generation);
+        }
+    }
+}
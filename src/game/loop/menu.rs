@@ -0,0 +1,61 @@
+// Copyright Claudio Mattera 2021.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Menu screen
+
+use crate::input::Mouse;
+use crate::interface::{draw_title, Button, CheckBox};
+
+use super::{draw_mouse_pointer, Screen};
+
+const ENTRIES: [(&str, Screen); 6] = [
+    ("Patterns", Screen::Patterns),
+    ("Breakpoints", Screen::Breakpoints),
+    ("Rules", Screen::Rules),
+    ("Slots", Screen::Slots),
+    ("Settings", Screen::Settings),
+    ("Back", Screen::World),
+];
+
+pub(super) fn update(
+    screen: &mut Screen,
+    heat_map: &mut bool,
+    statistics_panel: &mut bool,
+    preview: &mut bool,
+) {
+    draw_title();
+
+    let width = 4 + 8 * 12;
+    let x = (160 - width as i32) / 2;
+
+    let mut heat_map_checkbox = CheckBox::new("Heat map", x, 30, width, 4 + 8);
+    heat_map_checkbox.set_pressed(*heat_map);
+    heat_map_checkbox.toggle(&Mouse);
+    *heat_map = heat_map_checkbox.is_pressed();
+    heat_map_checkbox.draw(&Mouse);
+
+    let mut statistics_checkbox = CheckBox::new("Statistics", x, 44, width, 4 + 8);
+    statistics_checkbox.set_pressed(*statistics_panel);
+    statistics_checkbox.toggle(&Mouse);
+    *statistics_panel = statistics_checkbox.is_pressed();
+    statistics_checkbox.draw(&Mouse);
+
+    let mut preview_checkbox = CheckBox::new("Preview", x, 58, width, 4 + 8);
+    preview_checkbox.set_pressed(*preview);
+    preview_checkbox.toggle(&Mouse);
+    *preview = preview_checkbox.is_pressed();
+    preview_checkbox.draw(&Mouse);
+
+    for (i, (string, target)) in ENTRIES.iter().enumerate() {
+        let button = Button::new(string, x, 72 + 14 * i as i32, width, 4 + 8);
+        if button.is_released(&Mouse) {
+            *screen = *target;
+        }
+        button.draw(&Mouse);
+    }
+
+    draw_mouse_pointer();
+}
@@ -0,0 +1,125 @@
+// Copyright Claudio Mattera 2021.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Pattern library browser screen
+
+use crate::game::Pattern;
+use crate::graphics::{draw_rect, draw_text, set_drawing_colors};
+use crate::input::Mouse;
+use crate::interface::{Button, TextBuffer};
+use crate::library::LIBRARY;
+
+use super::{draw_centered_text, draw_mouse_pointer, Screen};
+
+/// Vertical position of the preview
+const PREVIEW_Y: i32 = 14;
+
+/// Height of the preview
+const PREVIEW_HEIGHT: i32 = 56;
+
+/// Vertical position of the first visible entry
+const ROWS_Y: i32 = 86;
+
+/// Height of the row of an entry
+const ROW_HEIGHT: i32 = 11;
+
+/// Number of entries visible at once
+const VISIBLE_ROWS: usize = 4;
+
+static mut SELECTED: usize = 0;
+static mut FIRST: usize = 0;
+
+/// Update the screen, returning a pattern to stamp if one was chosen
+pub(super) fn update(screen: &mut Screen) -> Option<Pattern> {
+    let selected = unsafe { &mut SELECTED };
+    let first = unsafe { &mut FIRST };
+
+    draw_centered_text("Patterns", 3);
+
+    let (mx, my) = Mouse.coordinates();
+    let (mx, my) = (mx as i32, my as i32);
+    for row in 0..VISIBLE_ROWS {
+        let index = *first + row;
+        let y = ROWS_Y + ROW_HEIGHT * row as i32;
+        if Mouse.left_clicked() && (y..y + ROW_HEIGHT).contains(&my) && (2..158).contains(&mx) {
+            *selected = index;
+        }
+        if index == *selected {
+            set_drawing_colors(0x30);
+            draw_rect(2, y - 1, 156, ROW_HEIGHT as u32);
+        }
+        set_drawing_colors(0x03);
+        draw_text(LIBRARY[index].name, 6, y + 1);
+    }
+
+    let entry = &LIBRARY[*selected];
+    let pattern = entry.pattern();
+    draw_preview(pattern);
+
+    let mut text = TextBuffer::<20>::new();
+    text.push_number(pattern.width() as u32)
+        .push_str("x")
+        .push_number(pattern.height() as u32)
+        .push_str(", ")
+        .push_number(pattern.population() as u32)
+        .push_str(" cells");
+    draw_centered_text(text.as_str(), PREVIEW_Y + PREVIEW_HEIGHT + 2);
+
+    let controls_y = 133;
+    let up_button = Button::new("^", 2, controls_y, 4 + 8, 4 + 8);
+    let down_button = Button::new("v", 18, controls_y, 4 + 8, 4 + 8);
+    let place_button = Button::new("Place", 54, controls_y, 4 + 8 * 6, 4 + 8);
+    let back_button = Button::new("Back", 106, controls_y, 4 + 8 * 6, 4 + 8);
+
+    if up_button.is_released(&Mouse) && *selected > 0 {
+        *selected -= 1;
+    }
+    if down_button.is_released(&Mouse) && *selected + 1 < LIBRARY.len() {
+        *selected += 1;
+    }
+    *first = (*first).min(*selected);
+    *first = (*first).max((*selected + 1).saturating_sub(VISIBLE_ROWS));
+
+    let mut placed = None;
+    if place_button.is_released(&Mouse) {
+        placed = Some(pattern.clone());
+        *screen = Screen::World;
+    }
+    if back_button.is_released(&Mouse) {
+        *screen = Screen::World;
+    }
+
+    up_button.draw(&Mouse);
+    down_button.draw(&Mouse);
+    place_button.draw(&Mouse);
+    back_button.draw(&Mouse);
+
+    draw_mouse_pointer();
+
+    placed
+}
+
+/// Draw a pattern centred in the preview, with cells as large as possible
+fn draw_preview(pattern: &Pattern) {
+    set_drawing_colors(0x41);
+    draw_rect(2, PREVIEW_Y, 156, PREVIEW_HEIGHT as u32);
+
+    let width = pattern.width().max(1) as i32;
+    let height = pattern.height().max(1) as i32;
+    let size = 4.min(154 / width).min((PREVIEW_HEIGHT - 2) / height).max(1);
+    let x = (160 - size * width) / 2;
+    let y = PREVIEW_Y + (PREVIEW_HEIGHT - size * height) / 2;
+
+    set_drawing_colors(2);
+    for (dx, dy) in pattern.live_cells() {
+        draw_rect(
+            x + dx as i32 * size,
+            y + dy as i32 * size,
+            size as u32,
+            size as u32,
+        );
+    }
+}
@@ -0,0 +1,180 @@
+// Copyright Claudio Mattera 2021.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Rule-space explorer screen
+
+use crate::analysis::{Behaviour, Explorer, Order, Random, Sample};
+use crate::game::Rule;
+use crate::graphics::{draw_text, set_drawing_colors};
+use crate::input::Mouse;
+use crate::interface::{draw_title, Button, CheckBox, TextBuffer};
+use crate::time::Ticker;
+
+use super::{draw_centered_text, draw_mouse_pointer, Screen};
+
+/// Maximal number of samples kept, older samples are discarded
+const CAPACITY: usize = 32;
+
+/// Number of samples shown in a page
+const PAGE_SIZE: usize = 4;
+
+/// Number of generations evolved in each frame while exploring
+const GENERATIONS_PER_FRAME: u32 = 8;
+
+/// Seed of the soup evolved under each rule
+const SOUP_SEED: u64 = 0;
+
+static mut EXPLORER: Explorer = Explorer::new(Order::Sequential(0), SOUP_SEED);
+/// Whether the explorer was started in the current order
+static mut STARTED: bool = false;
+static mut EXPLORING: bool = false;
+static mut RANDOM_ORDER: bool = false;
+static mut SAMPLES: [Option<Sample>; CAPACITY] = [None; CAPACITY];
+static mut SAMPLE_COUNT: usize = 0;
+static mut PAGE: usize = 0;
+
+/// Update the screen, returning whether the rule was changed
+pub(super) fn update(screen: &mut Screen, rule: &mut Rule) -> bool {
+    let explorer = unsafe { &mut EXPLORER };
+    let started = unsafe { &mut STARTED };
+    let exploring = unsafe { &mut EXPLORING };
+    let random_order = unsafe { &mut RANDOM_ORDER };
+    let samples = unsafe { &mut SAMPLES };
+    let sample_count = unsafe { &mut SAMPLE_COUNT };
+    let page = unsafe { &mut PAGE };
+
+    if *exploring {
+        if !*started {
+            explorer.restart(if *random_order {
+                Order::Random(Random::new(Ticker.get() as u64))
+            } else {
+                Order::Sequential(rule.index())
+            });
+            *started = true;
+        }
+        if let Some(sample) = explorer.advance(GENERATIONS_PER_FRAME) {
+            samples[*sample_count % CAPACITY] = Some(sample);
+            *sample_count += 1;
+        }
+    }
+
+    draw_title();
+
+    let stored = (*sample_count).min(CAPACITY);
+    let pages = stored.saturating_sub(1) / PAGE_SIZE + 1;
+    *page = (*page).min(pages - 1);
+
+    let mut changed = false;
+    for i in 0..PAGE_SIZE {
+        let position = *page * PAGE_SIZE + i;
+        if position >= stored {
+            break;
+        }
+        let sample = match samples[(*sample_count - 1 - position) % CAPACITY] {
+            Some(sample) => sample,
+            None => continue,
+        };
+        let y = 30 + 20 * i as i32;
+
+        let mut text = TextBuffer::<15>::new();
+        describe(&sample.rule, &mut text);
+        set_drawing_colors(0x03);
+        draw_text(text.as_str(), 4, y + 2);
+
+        let mut text = TextBuffer::<20>::new();
+        text.push_str(" ").push_str(sample.behaviour.name());
+        if let Behaviour::Periodic(period) = sample.behaviour {
+            text.push_str(" p").push_number(period as u32);
+        }
+        set_drawing_colors(0x02);
+        draw_text(text.as_str(), 4, y + 11);
+
+        let use_button = Button::new("Use", 128, y, 4 + 8 * 3, 4 + 8);
+        if use_button.is_released(&Mouse) && *rule != sample.rule {
+            *rule = sample.rule;
+            changed = true;
+        }
+        use_button.draw(&Mouse);
+    }
+
+    if stored == 0 {
+        draw_centered_text("No rules explored", 50);
+        draw_centered_text("Press Run to", 70);
+        draw_centered_text("start exploring", 80);
+    }
+
+    let row_y = 118;
+    let previous_button = Button::new("<", 8, row_y, 4 + 8, 4 + 8);
+    let next_button = Button::new(">", 56, row_y, 4 + 8, 4 + 8);
+    let mut run_checkbox = CheckBox::new("Run", 76, row_y, 4 + 8 * 3, 4 + 8);
+    run_checkbox.set_pressed(*exploring);
+    let order_button = Button::new(
+        if *random_order { "Rnd" } else { "Seq" },
+        120,
+        row_y,
+        4 + 8 * 3,
+        4 + 8,
+    );
+    let back_button = Button::new("Back", 54, 133, 4 + 8 * 6, 4 + 8);
+
+    if previous_button.is_released(&Mouse) && *page > 0 {
+        *page -= 1;
+    }
+    if next_button.is_released(&Mouse) && *page + 1 < pages {
+        *page += 1;
+    }
+    run_checkbox.toggle(&Mouse);
+    *exploring = run_checkbox.is_pressed();
+    if order_button.is_released(&Mouse) {
+        *random_order = !*random_order;
+        *started = false;
+    }
+    if back_button.is_released(&Mouse) {
+        *screen = Screen::World;
+    }
+
+    let mut text = TextBuffer::<5>::new();
+    text.push_number(*page as u32 + 1)
+        .push_str("/")
+        .push_number(pages as u32);
+    set_drawing_colors(0x03);
+    draw_text(text.as_str(), 22, row_y + 2);
+
+    previous_button.draw(&Mouse);
+    next_button.draw(&Mouse);
+    run_checkbox.draw(&Mouse);
+    order_button.draw(&Mouse);
+    back_button.draw(&Mouse);
+
+    let mut text = TextBuffer::<20>::new();
+    match explorer.current() {
+        Some(current) if *exploring && *started => {
+            text.push_str("Test ");
+            describe(&current, &mut text);
+        }
+        _ => {
+            text.push_str("Rule ");
+            describe(rule, &mut text);
+        }
+    }
+    draw_centered_text(text.as_str(), 148);
+
+    draw_mouse_pointer();
+
+    changed
+}
+
+/// Describe a rule in B/S notation
+pub(super) fn describe<const N: usize>(rule: &Rule, text: &mut TextBuffer<N>) {
+    text.push_str("B");
+    for count in Rule::counts(rule.birth()) {
+        text.push_number(count);
+    }
+    text.push_str("/S");
+    for count in Rule::counts(rule.survival()) {
+        text.push_number(count);
+    }
+}
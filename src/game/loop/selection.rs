@@ -0,0 +1,145 @@
+// Copyright Claudio Mattera 2021.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Selection tool of the world screen
+//!
+//! While the tool is active its toolbar replaces the title, and dragging
+//! with the left button selects a region of the world instead of drawing.
+//! A right click drops the selection.
+//! Copied cells stay in the clipboard until something else is copied, and
+//! pasting them starts a stamp.
+
+use crate::game::{Pattern, Region, Selection, Stamp, World};
+use crate::graphics::set_drawing_colors;
+use crate::input::Mouse;
+use crate::interface::{Button, CheckBox};
+
+use super::{draw_region_outline, world_cell};
+
+static mut ACTIVE: bool = false;
+static mut SELECTION: Option<Selection> = None;
+static mut DRAG_START: Option<(i16, i16)> = None;
+static mut CLIPBOARD: Option<Pattern> = None;
+
+/// Whether the selection tool is active
+pub(super) fn is_active() -> bool {
+    unsafe { ACTIVE }
+}
+
+fn create_checkbox() -> CheckBox {
+    let mut checkbox = CheckBox::new("Sel", 2, 118, 4 + 8 * 3, 4 + 8);
+    checkbox.set_pressed(is_active());
+    checkbox
+}
+
+/// Create the buttons of the toolbar, in two rows
+fn create_toolbar() -> [Button; 9] {
+    let width = 4 + 8 * 3;
+    [
+        Button::new("Copy", 9, 2, width + 8, 4 + 8),
+        Button::new("Cut", 47, 2, width, 4 + 8),
+        Button::new("Paste", 77, 2, width + 16, 4 + 8),
+        Button::new("Inv", 123, 2, width, 4 + 8),
+        Button::new("Clr", 2, 16, width, 4 + 8),
+        Button::new("Fill", 32, 16, width + 8, 4 + 8),
+        Button::new("Rot", 70, 16, width, 4 + 8),
+        Button::new("Hor", 100, 16, width, 4 + 8),
+        Button::new("Ver", 130, 16, width, 4 + 8),
+    ]
+}
+
+/// Toggle the selection tool, dropping the selection when leaving it
+pub(super) fn toggle() {
+    let mut checkbox = create_checkbox();
+    checkbox.toggle(&Mouse);
+    unsafe {
+        ACTIVE = checkbox.is_pressed();
+        if !ACTIVE {
+            SELECTION = None;
+            DRAG_START = None;
+        }
+    }
+}
+
+/// Operate the selection tool, returning whether the world changed
+///
+/// Pasting replaces the stamp with the content of the clipboard.
+pub(super) fn operate(world: &mut World, stamp: &mut Option<Stamp>, offset: (i32, i32)) -> bool {
+    let selection = unsafe { &mut SELECTION };
+    let drag_start = unsafe { &mut DRAG_START };
+    let clipboard = unsafe { &mut CLIPBOARD };
+
+    if Mouse.left_clicked() {
+        *drag_start = world_cell(&Mouse, offset);
+    }
+    if let Some(start) = *drag_start {
+        if let Some(end) = world_cell(&Mouse, offset) {
+            *selection = Some(Selection::new(Region::from_corners(start, end)));
+        }
+        if !Mouse.left_pressed() {
+            *drag_start = None;
+        }
+    }
+    if Mouse.right_clicked() {
+        *selection = None;
+    }
+
+    let [copy_button, cut_button, paste_button, invert_button, clear_button, fill_button, rotate_button, horizontal_button, vertical_button] =
+        create_toolbar();
+
+    if paste_button.is_released(&Mouse) {
+        if let Some(pattern) = clipboard {
+            *stamp = Some(Stamp::new(pattern.clone()));
+        }
+    }
+
+    let selection = match selection {
+        Some(selection) => selection,
+        None => return false,
+    };
+
+    let mut changed = true;
+    if copy_button.is_released(&Mouse) {
+        *clipboard = Some(selection.copy(world));
+        changed = false;
+    } else if cut_button.is_released(&Mouse) {
+        *clipboard = Some(selection.cut(world));
+    } else if invert_button.is_released(&Mouse) {
+        selection.invert(world);
+    } else if clear_button.is_released(&Mouse) {
+        selection.clear(world);
+    } else if fill_button.is_released(&Mouse) {
+        selection.fill(world);
+    } else if rotate_button.is_released(&Mouse) {
+        selection.rotate(world);
+    } else if horizontal_button.is_released(&Mouse) {
+        selection.reflect_horizontally(world);
+    } else if vertical_button.is_released(&Mouse) {
+        selection.reflect_vertically(world);
+    } else {
+        changed = false;
+    }
+    changed
+}
+
+/// Draw the checkbox of the tool, and its toolbar and the selection when
+/// active
+pub(super) fn draw(offset: (i32, i32), stamping: bool) {
+    create_checkbox().draw(&Mouse);
+
+    if !is_active() || stamping {
+        return;
+    }
+
+    for button in create_toolbar().iter() {
+        button.draw(&Mouse);
+    }
+
+    if let Some(selection) = unsafe { SELECTION } {
+        set_drawing_colors(0x30);
+        draw_region_outline(selection.region(), offset);
+    }
+}
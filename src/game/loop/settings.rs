@@ -0,0 +1,66 @@
+// Copyright Claudio Mattera 2021.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Settings screen
+
+use crate::game::{Settings, Topology};
+use crate::graphics::Palette;
+use crate::input::Mouse;
+use crate::interface::Button;
+
+use super::{draw_centered_text, draw_mouse_pointer, Screen};
+
+/// Update the screen, returning whether the evolution of the world changed
+pub(super) fn update(screen: &mut Screen, settings: &mut Settings) -> bool {
+    let mut changed = false;
+
+    draw_centered_text("Settings", 3);
+
+    draw_centered_text("Palette", 22);
+    let previous_button = Button::new("<", 10, 34, 4 + 8, 4 + 8);
+    let next_button = Button::new(">", 138, 34, 4 + 8, 4 + 8);
+    let count = Palette::ALL.len();
+    let index = settings.palette.index();
+    if previous_button.is_released(&Mouse) {
+        settings.palette = Palette::ALL[(index + count - 1) % count];
+    }
+    if next_button.is_released(&Mouse) {
+        settings.palette = Palette::ALL[(index + 1) % count];
+    }
+    previous_button.draw(&Mouse);
+    next_button.draw(&Mouse);
+    draw_centered_text(settings.palette.name(), 36);
+
+    draw_centered_text("Topology", 58);
+    let width = 4 + 8 * 12;
+    let x = (160 - width as i32) / 2;
+    let topology_button = Button::new(settings.topology.name(), x, 70, width, 4 + 8);
+    if topology_button.is_released(&Mouse) {
+        settings.topology = match settings.topology {
+            Topology::Torus => Topology::Plane,
+            Topology::Plane => Topology::Torus,
+        };
+        changed = true;
+    }
+    topology_button.draw(&Mouse);
+
+    let reset_button = Button::new("Reset", 24, 133, 4 + 8 * 6, 4 + 8);
+    let back_button = Button::new("Back", 84, 133, 4 + 8 * 6, 4 + 8);
+    if reset_button.is_released(&Mouse) {
+        changed = settings.rule != Settings::DEFAULT.rule
+            || settings.topology != Settings::DEFAULT.topology;
+        *settings = Settings::DEFAULT;
+    }
+    if back_button.is_released(&Mouse) {
+        *screen = Screen::World;
+    }
+    reset_button.draw(&Mouse);
+    back_button.draw(&Mouse);
+
+    draw_mouse_pointer();
+
+    changed
+}
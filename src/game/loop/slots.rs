@@ -0,0 +1,228 @@
+// Copyright Claudio Mattera 2021.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Save slots screen
+
+use crate::game::{read_slot, LoadError, Save, World, SLOT_COUNT};
+use crate::graphics::{draw_point, draw_rect, draw_text, set_drawing_colors};
+use crate::input::Mouse;
+use crate::interface::{Button, TextBuffer};
+use crate::storage::Disk;
+
+use super::{draw_centered_text, draw_mouse_pointer, rules, Screen};
+
+/// Height of the row of a slot
+const ROW_HEIGHT: i32 = 22;
+
+/// Vertical position of the first slot
+const ROWS_Y: i32 = 14;
+
+/// An action on a slot
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Action {
+    Save,
+    Load,
+    Delete,
+}
+
+/// Whether the disk must be read again, every time the screen is opened
+static mut STALE: bool = true;
+static mut SELECTED: usize = 0;
+static mut CONFIRMING: Option<Action> = None;
+static mut MESSAGE: Option<&str> = None;
+
+/// Save of the slot being drawn or selected
+static mut SLOT: Save = Save::EMPTY;
+
+/// Update the screen, returning whether the current save was replaced by the
+/// one in a slot
+pub(super) fn update(
+    screen: &mut Screen,
+    image: &mut [u8; Disk::SIZE],
+    current: &mut Save,
+) -> bool {
+    let scratch = unsafe { &mut SLOT };
+    let stale = unsafe { &mut STALE };
+    let selected = unsafe { &mut SELECTED };
+    let confirming = unsafe { &mut CONFIRMING };
+    let message = unsafe { &mut MESSAGE };
+
+    if *stale {
+        for byte in image.iter_mut() {
+            *byte = 0;
+        }
+        Disk.read(image);
+        *stale = false;
+    }
+
+    draw_centered_text("Save slots", 3);
+
+    for slot in 0..SLOT_COUNT {
+        let y = ROWS_Y + ROW_HEIGHT * slot as i32;
+        let (mx, my) = Mouse.coordinates();
+        let (mx, my) = (mx as i32, my as i32);
+        if Mouse.left_clicked() && (y..y + ROW_HEIGHT).contains(&my) && (2..158).contains(&mx) {
+            *selected = slot;
+            *confirming = None;
+            *message = None;
+        }
+        let content = read_slot(image, slot, scratch).map(|()| &*scratch);
+        draw_slot(slot, content, y, slot == *selected);
+    }
+
+    let slot = *selected;
+    let content = read_slot(image, slot, scratch);
+    let mut loaded = false;
+
+    let controls_y = ROWS_Y + ROW_HEIGHT * SLOT_COUNT as i32 + 4;
+    if let Some(action) = *confirming {
+        let yes_button = Button::new("Yes", 42, controls_y, 4 + 8 * 4, 4 + 8);
+        let no_button = Button::new("No", 82, controls_y, 4 + 8 * 4, 4 + 8);
+        if yes_button.is_released(&Mouse) {
+            *confirming = None;
+            match action {
+                Action::Save => *message = Some(save_slot(image, slot, current)),
+                Action::Load => match content {
+                    Ok(()) => {
+                        current.replace(scratch);
+                        loaded = true;
+                        *message = Some("Loaded");
+                    }
+                    Err(error) => *message = Some(error.name()),
+                },
+                Action::Delete => {
+                    let buffer = [0; Save::MAX_SIZE];
+                    *message = Some(write_slot(image, slot, &buffer, "Deleted", "Delete failed"));
+                }
+            }
+        } else if no_button.is_released(&Mouse) {
+            *confirming = None;
+        }
+        yes_button.draw(&Mouse);
+        no_button.draw(&Mouse);
+
+        let mut text = TextBuffer::<20>::new();
+        text.push_str(match action {
+            Action::Save => "Overwrite slot ",
+            Action::Load => "Load slot ",
+            Action::Delete => "Delete slot ",
+        });
+        text.push_number(slot as u32 + 1).push_str("?");
+        draw_centered_text(text.as_str(), 148);
+    } else {
+        let save_button = Button::new("Save", 2, controls_y, 4 + 8 * 4, 4 + 8);
+        let load_button = Button::new("Load", 42, controls_y, 4 + 8 * 4, 4 + 8);
+        let delete_button = Button::new("Del", 82, controls_y, 4 + 8 * 4, 4 + 8);
+        let back_button = Button::new("Back", 122, controls_y, 4 + 8 * 4, 4 + 8);
+
+        if save_button.is_released(&Mouse) {
+            if content == Err(LoadError::Empty) {
+                *message = Some(save_slot(image, slot, current));
+            } else {
+                *confirming = Some(Action::Save);
+            }
+        }
+        if load_button.is_released(&Mouse) {
+            match &content {
+                Ok(_) => *confirming = Some(Action::Load),
+                Err(error) => *message = Some(error.name()),
+            }
+        }
+        if delete_button.is_released(&Mouse) {
+            if content == Err(LoadError::Empty) {
+                *message = Some(LoadError::Empty.name());
+            } else {
+                *confirming = Some(Action::Delete);
+            }
+        }
+        if back_button.is_released(&Mouse) {
+            *screen = Screen::World;
+            *stale = true;
+            *message = None;
+        }
+
+        save_button.draw(&Mouse);
+        load_button.draw(&Mouse);
+        delete_button.draw(&Mouse);
+        back_button.draw(&Mouse);
+
+        if let Some(message) = message {
+            draw_centered_text(message, 148);
+        }
+    }
+
+    draw_mouse_pointer();
+
+    loaded
+}
+
+/// Write the current save to a slot, choosing a message for the outcome
+fn save_slot(image: &mut [u8; Disk::SIZE], slot: usize, current: &Save) -> &'static str {
+    let mut buffer = [0; Save::MAX_SIZE];
+    current.encode(&mut buffer);
+    write_slot(image, slot, &buffer, "Saved", "Save failed")
+}
+
+/// Write an encoded save to a slot of the disk, choosing a message for the
+/// outcome
+///
+/// Only the slot is written, so the rest of the disk is kept even if the
+/// image is outdated; afterwards the image holds the new content of the disk.
+fn write_slot(
+    image: &mut [u8; Disk::SIZE],
+    slot: usize,
+    buffer: &[u8; Save::MAX_SIZE],
+    success: &'static str,
+    failure: &'static str,
+) -> &'static str {
+    if Disk.write_at(image, slot * Save::MAX_SIZE, buffer) {
+        success
+    } else {
+        failure
+    }
+}
+
+/// Draw a slot as a thumbnail of the world followed by its generation and rule
+fn draw_slot(slot: usize, content: Result<&Save, LoadError>, y: i32, selected: bool) {
+    if selected {
+        set_drawing_colors(0x30);
+        draw_rect(2, y - 1, 156, ROW_HEIGHT as u32);
+    }
+
+    set_drawing_colors(0x41);
+    draw_rect(4, y, World::WIDTH as u32 + 2, World::HEIGHT as u32 + 2);
+
+    let mut first_line = TextBuffer::<14>::new();
+    first_line.push_number(slot as u32 + 1).push_str(" ");
+    let mut second_line = TextBuffer::<14>::new();
+    match content {
+        Ok(save) => {
+            set_drawing_colors(0x02);
+            for y_cell in 0..World::HEIGHT as i16 {
+                for x_cell in 0..World::WIDTH as i16 {
+                    if save.world.get_cell(x_cell, y_cell) {
+                        draw_point(5 + x_cell as i32, y + 1 + y_cell as i32);
+                    }
+                }
+            }
+            first_line.push_str("Gen ").push_number(save.generation);
+            rules::describe(&save.rule, &mut second_line);
+        }
+        Err(LoadError::Empty) => {
+            first_line.push_str("Empty");
+        }
+        Err(error) => {
+            first_line.push_str("Unreadable");
+            second_line.push_str(error.name());
+        }
+    }
+
+    let text_x = 4 + World::WIDTH as i32 + 4;
+    set_drawing_colors(0x03);
+    draw_text(first_line.as_str(), text_x, y + 2);
+    set_drawing_colors(0x02);
+    draw_text(second_line.as_str(), text_x, y + 11);
+}
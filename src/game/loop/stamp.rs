@@ -0,0 +1,77 @@
+// Copyright Claudio Mattera 2021.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Stamp tool of the world screen
+//!
+//! While a stamp is active its toolbar replaces the title, the stamp follows
+//! the mouse pointer and a left click places it.
+//! A right click or the "Done" button drops the stamp.
+
+use crate::game::{Region, Stamp, World};
+use crate::graphics::set_drawing_colors;
+use crate::input::Mouse;
+use crate::interface::Button;
+
+use super::{draw_region_outline, world_cell};
+
+/// Create the buttons of the toolbar
+fn create_toolbar() -> [Button; 4] {
+    let y = 8;
+    let width = 4 + 8 * 3;
+    let x = (160 - (4 * width as i32 + 8) - 3 * 6) / 2;
+    [
+        Button::new("Rot", x, y, width, 4 + 8),
+        Button::new("Hor", x + (width as i32 + 6), y, width, 4 + 8),
+        Button::new("Ver", x + 2 * (width as i32 + 6), y, width, 4 + 8),
+        Button::new("Done", x + 3 * (width as i32 + 6), y, width + 8, 4 + 8),
+    ]
+}
+
+/// Operate the stamp, returning whether the world changed
+pub(super) fn operate(stamp: &mut Option<Stamp>, world: &mut World, offset: (i32, i32)) -> bool {
+    let active = match stamp {
+        Some(active) => active,
+        None => return false,
+    };
+
+    let [rotate_button, horizontal_button, vertical_button, done_button] = create_toolbar();
+    if rotate_button.is_released(&Mouse) {
+        active.rotate();
+    }
+    if horizontal_button.is_released(&Mouse) {
+        active.reflect_horizontally();
+    }
+    if vertical_button.is_released(&Mouse) {
+        active.reflect_vertically();
+    }
+
+    let mut changed = false;
+    if Mouse.left_clicked() {
+        if let Some((x, y)) = world_cell(&Mouse, offset) {
+            active.place(world, x, y);
+            changed = true;
+        }
+    }
+
+    if done_button.is_released(&Mouse) || Mouse.right_clicked() {
+        *stamp = None;
+    }
+    changed
+}
+
+/// Draw the toolbar and outline the cells covered by the stamp
+pub(super) fn draw(stamp: &Stamp, offset: (i32, i32)) {
+    for button in create_toolbar().iter() {
+        button.draw(&Mouse);
+    }
+
+    if let Some((x, y)) = world_cell(&Mouse, offset) {
+        set_drawing_colors(0x40);
+        for (x, y) in stamp.cells(x, y) {
+            draw_region_outline(Region::new(x, y, 1, 1), offset);
+        }
+    }
+}
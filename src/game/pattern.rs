@@ -0,0 +1,238 @@
+// Copyright Claudio Mattera 2021.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Patterns of cells
+
+use super::world::fnv1a;
+use super::World;
+
+/// A rectangular pattern of cells
+///
+/// A pattern is at most as large as the world, in either orientation.
+/// Cells are packed in bits, so patterns are much smaller than worlds.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Pattern {
+    width: usize,
+    height: usize,
+    bits: [u64; Pattern::WORDS],
+}
+
+impl Pattern {
+    const WORDS: usize = World::WIDTH * World::HEIGHT / 64 + 1;
+
+    /// Create an empty pattern
+    ///
+    /// The size is clamped so that the pattern contains at most as many cells
+    /// as the world.
+    pub const fn new(width: usize, height: usize) -> Self {
+        let height = if width * height > World::WIDTH * World::HEIGHT {
+            World::WIDTH * World::HEIGHT / width
+        } else {
+            height
+        };
+        Self {
+            width,
+            height,
+            bits: [0; Self::WORDS],
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get_cell(&self, x: usize, y: usize) -> bool {
+        if x >= self.width || y >= self.height {
+            return false;
+        }
+        let i = y * self.width + x;
+        self.bits[i / 64] & (1 << (i % 64)) != 0
+    }
+
+    pub fn set_cell(&mut self, x: usize, y: usize, value: bool) {
+        if x >= self.width || y >= self.height {
+            return;
+        }
+        let i = y * self.width + x;
+        if value {
+            self.bits[i / 64] |= 1 << (i % 64);
+        } else {
+            self.bits[i / 64] &= !(1 << (i % 64));
+        }
+    }
+
+    /// Return the pattern with a cell made alive, ignoring cells outside it
+    ///
+    /// Unlike [`Pattern::set_cell`] this can be evaluated at compile time.
+    pub const fn with_cell(mut self, x: usize, y: usize) -> Self {
+        if x < self.width && y < self.height {
+            let i = y * self.width + x;
+            self.bits[i / 64] |= 1 << (i % 64);
+        }
+        self
+    }
+
+    pub fn population(&self) -> usize {
+        self.bits
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+
+    /// Iterate over the coordinates of all live cells
+    pub fn live_cells(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        (0..self.height).flat_map(move |y| {
+            (0..self.width)
+                .filter(move |x| self.get_cell(*x, y))
+                .map(move |x| (x, y))
+        })
+    }
+
+    /// Apply one of the eight symmetries of the square
+    ///
+    /// The pattern is first transposed, then reflected around its vertical
+    /// axis and finally reflected around its horizontal axis, each step only
+    /// if requested.
+    pub fn transformed(&self, transpose: bool, flip_x: bool, flip_y: bool) -> Self {
+        let (width, height) = if transpose {
+            (self.height, self.width)
+        } else {
+            (self.width, self.height)
+        };
+        let mut pattern = Self::new(width, height);
+        for y in 0..height {
+            for x in 0..width {
+                let x1 = if flip_x { width - 1 - x } else { x };
+                let y1 = if flip_y { height - 1 - y } else { y };
+                let (x1, y1) = if transpose { (y1, x1) } else { (x1, y1) };
+                if self.get_cell(x1, y1) {
+                    pattern.set_cell(x, y, true);
+                }
+            }
+        }
+        pattern
+    }
+
+    /// Rotate the pattern by 90 degrees clockwise
+    pub fn rotated(&self) -> Self {
+        self.transformed(true, true, false)
+    }
+
+    /// Reflect the pattern around its vertical axis
+    pub fn reflected_horizontally(&self) -> Self {
+        self.transformed(false, true, false)
+    }
+
+    /// Reflect the pattern around its horizontal axis
+    pub fn reflected_vertically(&self) -> Self {
+        self.transformed(false, false, true)
+    }
+
+    /// Compute a 64-bit hash of the pattern
+    pub fn hash(&self) -> u64 {
+        let size = [self.width as u8, self.height as u8];
+        let bytes = self.bits.iter().flat_map(|word| word.to_le_bytes());
+        fnv1a(IntoIterator::into_iter(size).chain(bytes))
+    }
+
+    /// Compute a hash invariant under rotations and reflections
+    ///
+    /// The hash is the smallest among the hashes of the eight orientations of
+    /// the pattern.
+    pub fn canonical_hash(&self) -> u64 {
+        (0..8)
+            .map(|symmetry| {
+                self.transformed(symmetry & 4 != 0, symmetry & 2 != 0, symmetry & 1 != 0)
+                    .hash()
+            })
+            .min()
+            .unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn glider() -> Pattern {
+        let mut pattern = Pattern::new(3, 3);
+        pattern.set_cell(1, 0, true);
+        pattern.set_cell(2, 1, true);
+        pattern.set_cell(0, 2, true);
+        pattern.set_cell(1, 2, true);
+        pattern.set_cell(2, 2, true);
+        pattern
+    }
+
+    #[test]
+    fn set_and_get() {
+        let mut pattern = Pattern::new(3, 2);
+        pattern.set_cell(2, 1, true);
+        assert!(pattern.get_cell(2, 1));
+        pattern.set_cell(2, 1, false);
+        assert!(!pattern.get_cell(2, 1));
+        assert!(!pattern.get_cell(5, 5));
+    }
+
+    #[test]
+    fn rotated() {
+        let mut pattern = Pattern::new(3, 1);
+        pattern.set_cell(0, 0, true);
+
+        let rotated = pattern.rotated();
+
+        assert_eq!((rotated.width(), rotated.height()), (1, 3));
+        assert!(rotated.get_cell(0, 0));
+        assert_eq!(rotated.population(), 1);
+
+        let rotated = rotated.rotated();
+
+        assert!(rotated.get_cell(2, 0));
+    }
+
+    #[test]
+    fn four_rotations_are_identity() {
+        let pattern = glider();
+        let actual = pattern.rotated().rotated().rotated().rotated();
+        assert_eq!(actual, pattern);
+    }
+
+    #[test]
+    fn reflections() {
+        let pattern = glider();
+        assert!(pattern.reflected_horizontally().get_cell(0, 1));
+        assert!(!pattern.reflected_horizontally().get_cell(2, 1));
+        assert!(pattern.reflected_vertically().get_cell(1, 2));
+    }
+
+    #[test]
+    fn canonical_hash_is_invariant() {
+        let pattern = glider();
+        let expected = pattern.canonical_hash();
+
+        assert_eq!(pattern.rotated().canonical_hash(), expected);
+        assert_eq!(pattern.reflected_horizontally().canonical_hash(), expected);
+        assert_eq!(
+            pattern.rotated().reflected_vertically().canonical_hash(),
+            expected
+        );
+        assert_ne!(pattern.hash(), pattern.rotated().hash());
+    }
+
+    #[test]
+    fn canonical_hash_distinguishes_patterns() {
+        let mut blinker = Pattern::new(3, 1);
+        blinker.set_cell(0, 0, true);
+        blinker.set_cell(1, 0, true);
+        blinker.set_cell(2, 0, true);
+
+        assert_ne!(blinker.canonical_hash(), glider().canonical_hash());
+    }
+}
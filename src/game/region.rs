@@ -0,0 +1,99 @@
+// Copyright Claudio Mattera 2021.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Rectangular regions of the world
+
+use super::World;
+
+/// A rectangular region of the world
+///
+/// Regions can extend past the right and bottom edges of the world, in which
+/// case they wrap around to the opposite edges.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Region {
+    pub x: i16,
+    pub y: i16,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl Region {
+    pub const fn new(x: i16, y: i16, width: usize, height: usize) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// Create a region covering the whole world
+    pub const fn world() -> Self {
+        Self::new(0, 0, World::WIDTH, World::HEIGHT)
+    }
+
+    /// Create the smallest region containing two opposite corners
+    pub fn from_corners(first: (i16, i16), second: (i16, i16)) -> Self {
+        let x = first.0.min(second.0);
+        let y = first.1.min(second.1);
+        let width = (first.0 - second.0).unsigned_abs() as usize + 1;
+        let height = (first.1 - second.1).unsigned_abs() as usize + 1;
+        Self::new(x, y, width, height)
+    }
+
+    /// Check whether a cell lies inside the region
+    pub fn contains(&self, x: i16, y: i16) -> bool {
+        let dx = (x - self.x).rem_euclid(World::WIDTH as i16) as usize;
+        let dy = (y - self.y).rem_euclid(World::HEIGHT as i16) as usize;
+        dx < self.width && dy < self.height
+    }
+
+    /// Iterate over the cells of the region, wrapped inside the world
+    pub fn cells(&self) -> impl Iterator<Item = (i16, i16)> {
+        let Region {
+            x,
+            y,
+            width,
+            height,
+        } = *self;
+        (0..height).flat_map(move |dy| {
+            (0..width).map(move |dx| {
+                (
+                    (x + dx as i16).rem_euclid(World::WIDTH as i16),
+                    (y + dy as i16).rem_euclid(World::HEIGHT as i16),
+                )
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_corners() {
+        let actual = Region::from_corners((5, 2), (3, 6));
+        let expected = Region::new(3, 2, 3, 5);
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn cells_wrap_around_the_edges() {
+        let region = Region::new(World::WIDTH as i16 - 1, 0, 2, 1);
+        let mut cells = region.cells();
+        assert_eq!(cells.next(), Some((World::WIDTH as i16 - 1, 0)));
+        assert_eq!(cells.next(), Some((0, 0)));
+        assert_eq!(cells.next(), None);
+    }
+
+    #[test]
+    fn contains_wrapped_cell() {
+        let region = Region::new(World::WIDTH as i16 - 1, 0, 2, 1);
+        assert!(region.contains(0, 0));
+        assert!(!region.contains(1, 0));
+    }
+}
@@ -0,0 +1,171 @@
+// Copyright Claudio Mattera 2021.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Life-like rules
+
+use core::fmt;
+
+/// A Life-like rule
+///
+/// A rule specifies for how many live neighbours a dead cell is born and a
+/// live cell survives.
+/// Each set of neighbour counts is stored as a bit mask, where bit `n` is set
+/// when the count `n` is included.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Rule {
+    birth: u16,
+    survival: u16,
+}
+
+impl Rule {
+    /// Conway's Game of Life, B3/S23
+    pub const CONWAY: Rule = Rule::new(0b1000, 0b1100);
+
+    /// Number of different Life-like rules
+    pub const COUNT: u32 = 1 << 18;
+
+    pub const fn new(birth: u16, survival: u16) -> Self {
+        Self {
+            birth: birth & 0x1ff,
+            survival: survival & 0x1ff,
+        }
+    }
+
+    /// Create the rule with a given index in `0..Rule::COUNT`
+    ///
+    /// The lower nine bits of the index are the birth mask, the upper nine bits
+    /// are the survival mask.
+    pub const fn from_index(index: u32) -> Self {
+        Self::new(index as u16, (index >> 9) as u16)
+    }
+
+    pub const fn index(&self) -> u32 {
+        self.birth as u32 | (self.survival as u32) << 9
+    }
+
+    pub const fn birth(&self) -> u16 {
+        self.birth
+    }
+
+    pub const fn survival(&self) -> u16 {
+        self.survival
+    }
+
+    /// Iterate over the neighbour counts included in a mask, in increasing
+    /// order
+    pub fn counts(mask: u16) -> impl Iterator<Item = u32> {
+        (0..9).filter(move |count| mask & (1 << count) != 0)
+    }
+
+    /// Compute the next state of a cell from its live neighbours
+    pub const fn next(&self, alive: bool, count: usize) -> bool {
+        let mask = if alive { self.survival } else { self.birth };
+        mask & (1 << count) != 0
+    }
+
+    /// Parse a rule in B/S notation, e.g. `B3/S23`
+    ///
+    /// The letters are case-insensitive, and the S/B notation, e.g. `23/3`, is
+    /// also accepted.
+    pub fn parse(string: &str) -> Option<Self> {
+        let mut parts = string.trim().split('/');
+        let first = parts.next()?;
+        let second = parts.next()?;
+        if parts.next().is_some() {
+            return None;
+        }
+
+        let first_letter = first.bytes().next().map(|byte| byte.to_ascii_uppercase());
+        let second_letter = second.bytes().next().map(|byte| byte.to_ascii_uppercase());
+        match (first_letter, second_letter) {
+            (Some(b'B'), Some(b'S')) => Some(Self::new(mask(&first[1..])?, mask(&second[1..])?)),
+            (Some(b'S'), Some(b'B')) => Some(Self::new(mask(&second[1..])?, mask(&first[1..])?)),
+            _ => Some(Self::new(mask(second)?, mask(first)?)),
+        }
+    }
+}
+
+impl Default for Rule {
+    fn default() -> Self {
+        Self::CONWAY
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("B")?;
+        for count in Self::counts(self.birth) {
+            write!(f, "{}", count)?;
+        }
+        f.write_str("/S")?;
+        for count in Self::counts(self.survival) {
+            write!(f, "{}", count)?;
+        }
+        Ok(())
+    }
+}
+
+/// Parse a sequence of neighbour counts into a bit mask
+fn mask(digits: &str) -> Option<u16> {
+    digits.bytes().try_fold(0, |mask, digit| match digit {
+        b'0'..=b'8' => Some(mask | 1 << (digit - b'0')),
+        _ => None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn conway() {
+        let rule = Rule::CONWAY;
+
+        assert!(rule.next(false, 3));
+        assert!(!rule.next(false, 2));
+        assert!(rule.next(true, 2));
+        assert!(rule.next(true, 3));
+        assert!(!rule.next(true, 4));
+    }
+
+    #[test]
+    fn counts() {
+        let actual: Vec<u32> = Rule::counts(0b1_0000_1001).collect();
+
+        assert_eq!(actual, [0, 3, 8]);
+        assert_eq!(Rule::counts(0).count(), 0);
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!(Rule::parse("B3/S23"), Some(Rule::CONWAY));
+        assert_eq!(Rule::parse("b3/s23"), Some(Rule::CONWAY));
+        assert_eq!(Rule::parse("S23/B3"), Some(Rule::CONWAY));
+        assert_eq!(Rule::parse("23/3"), Some(Rule::CONWAY));
+        assert_eq!(Rule::parse("B2/S"), Some(Rule::new(0b100, 0)));
+        assert_eq!(Rule::parse("B9/S23"), None);
+        assert_eq!(Rule::parse("B3S23"), None);
+    }
+
+    #[test]
+    fn index_round_trip() {
+        let rule = Rule::parse("B36/S23").unwrap();
+
+        let actual = Rule::from_index(rule.index());
+
+        assert_eq!(actual, rule);
+        assert_eq!(Rule::from_index(Rule::COUNT - 1).index(), Rule::COUNT - 1);
+    }
+
+    #[test]
+    fn display() {
+        let rule = Rule::parse("B36/S023").unwrap();
+
+        let actual = rule.to_string();
+
+        assert_eq!(actual, "B36/S023");
+    }
+}
@@ -0,0 +1,436 @@
+// Copyright Claudio Mattera 2021.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Saved games
+//!
+//! A save is stored as a header, a payload and a checksum.
+//!
+//! ~~~~text
+//! offset  size  content
+//! 0       3     magic bytes "GoL"
+//! 3       1     version
+//! 4       2     length of the payload, little endian
+//! 6       n     payload
+//! 6 + n   2     Fletcher-16 checksum of all previous bytes, little endian
+//! ~~~~
+//!
+//! The payload contains the rule index and the generation as little-endian
+//! 32-bit numbers, the speed, a byte of flags and the cells of the world.
+//! Version 1 stores the cells packed eight per byte in row-major order,
+//! version 2 stores them with the compact encoding of [`encode_world`].
+//!
+//! The disk is divided into [`SLOT_COUNT`] slots of [`Save::MAX_SIZE`] bytes,
+//! each holding a save, and a slot filled with zeros is empty.
+
+use super::encoding::{decode_world_into, unpack_world, PACKED_SIZE};
+use super::{encode_world, Rule, World, MAX_ENCODED_SIZE};
+
+const MAGIC: &[u8; 3] = b"GoL";
+const VERSION: u8 = 2;
+pub(super) const HEADER_SIZE: usize = 6;
+pub(super) const CHECKSUM_SIZE: usize = 2;
+const STATE_SIZE: usize = 4 + 4 + 1 + 1;
+const MAX_PAYLOAD_SIZE: usize = STATE_SIZE + MAX_ENCODED_SIZE;
+
+const PAUSED: u8 = 1 << 0;
+const AUTO_PAUSE: u8 = 1 << 1;
+const HEAT_MAP: u8 = 1 << 2;
+const STATISTICS_PANEL: u8 = 1 << 3;
+const PREVIEW: u8 = 1 << 4;
+
+/// Number of save slots on the disk
+pub const SLOT_COUNT: usize = 5;
+
+/// Number of bytes at the start of the disk taken by the save slots
+pub const SLOTS_SIZE: usize = SLOT_COUNT * Save::MAX_SIZE;
+
+/// The state of a game to persist across sessions
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Save {
+    pub world: World,
+    pub rule: Rule,
+    pub generation: u32,
+    pub speed: u8,
+    pub paused: bool,
+    pub auto_pause: bool,
+    pub heat_map: bool,
+    pub statistics_panel: bool,
+    pub preview: bool,
+}
+
+/// A reason for rejecting a save
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LoadError {
+    /// Nothing was saved
+    Empty,
+
+    /// The data does not start with the magic bytes
+    InvalidMagic,
+
+    /// The save was written by a different version of the game
+    UnsupportedVersion(u8),
+
+    /// The data is shorter than declared in the header
+    Truncated,
+
+    /// The checksum or the payload is invalid
+    Corrupted,
+}
+
+impl LoadError {
+    /// Get a short description of the error
+    pub fn name(&self) -> &'static str {
+        match self {
+            LoadError::Empty => "No save",
+            LoadError::InvalidMagic => "Invalid save",
+            LoadError::UnsupportedVersion(_) => "Old save",
+            LoadError::Truncated => "Truncated save",
+            LoadError::Corrupted => "Corrupted save",
+        }
+    }
+}
+
+impl Save {
+    /// Maximal size of an encoded save
+    pub const MAX_SIZE: usize = HEADER_SIZE + MAX_PAYLOAD_SIZE + CHECKSUM_SIZE;
+
+    /// Encode the save into a buffer, returning the number of bytes used
+    pub fn encode(&self, buffer: &mut [u8; Save::MAX_SIZE]) -> usize {
+        let payload = &mut buffer[HEADER_SIZE..];
+        payload[0..4].copy_from_slice(&self.rule.index().to_le_bytes());
+        payload[4..8].copy_from_slice(&self.generation.to_le_bytes());
+        payload[8] = self.speed;
+        payload[9] = flag(self.paused, PAUSED)
+            | flag(self.auto_pause, AUTO_PAUSE)
+            | flag(self.heat_map, HEAT_MAP)
+            | flag(self.statistics_panel, STATISTICS_PANEL)
+            | flag(self.preview, PREVIEW);
+
+        let mut world = [0; MAX_ENCODED_SIZE];
+        let world_size = encode_world(&self.world, &mut world);
+        payload[STATE_SIZE..STATE_SIZE + world_size].copy_from_slice(&world[..world_size]);
+
+        seal(buffer, MAGIC, VERSION, STATE_SIZE + world_size)
+    }
+
+    /// An empty world with the default state, to decode saves into
+    pub const EMPTY: Save = Save {
+        world: World::new(),
+        rule: Rule::CONWAY,
+        generation: 0,
+        speed: 1,
+        paused: false,
+        auto_pause: false,
+        heat_map: false,
+        statistics_panel: false,
+        preview: false,
+    };
+
+    /// Copy another save into this one
+    pub fn replace(&mut self, other: &Self) {
+        self.world.replace(&other.world);
+        self.rule = other.rule;
+        self.generation = other.generation;
+        self.speed = other.speed;
+        self.paused = other.paused;
+        self.auto_pause = other.auto_pause;
+        self.heat_map = other.heat_map;
+        self.statistics_panel = other.statistics_panel;
+        self.preview = other.preview;
+    }
+
+    /// Decode a save, checking its version and checksum
+    ///
+    /// Saves of all previous versions are still accepted.
+    pub fn decode(data: &[u8]) -> Result<Self, LoadError> {
+        let mut save = Self::EMPTY;
+        save.decode_into(data)?;
+        Ok(save)
+    }
+
+    /// Decode a save in place, checking its version and checksum
+    ///
+    /// The save may be partially overwritten when decoding fails.
+    pub fn decode_into(&mut self, data: &[u8]) -> Result<(), LoadError> {
+        let (version, payload) = unseal(data, MAGIC, VERSION)?;
+        if payload.len() < STATE_SIZE {
+            return Err(LoadError::Corrupted);
+        }
+
+        let index = u32::from_le_bytes([payload[0], payload[1], payload[2], payload[3]]);
+        if index >= Rule::COUNT {
+            return Err(LoadError::Corrupted);
+        }
+        let generation = u32::from_le_bytes([payload[4], payload[5], payload[6], payload[7]]);
+        let flags = payload[9];
+
+        self.rule = Rule::from_index(index);
+        self.generation = generation;
+        self.speed = payload[8];
+        self.paused = flags & PAUSED != 0;
+        self.auto_pause = flags & AUTO_PAUSE != 0;
+        self.heat_map = flags & HEAT_MAP != 0;
+        self.statistics_panel = flags & STATISTICS_PANEL != 0;
+        self.preview = flags & PREVIEW != 0;
+
+        let cells = &payload[STATE_SIZE..];
+        let decoded = match version {
+            1 if cells.len() == PACKED_SIZE => {
+                unpack_world(cells, &mut self.world);
+                Some(())
+            }
+            1 => None,
+            _ => decode_world_into(cells, &mut self.world),
+        };
+        decoded.ok_or(LoadError::Corrupted)
+    }
+}
+
+/// Write the header and the checksum around a payload already in a buffer
+///
+/// The payload starts after [`HEADER_SIZE`] bytes, and the checksum takes
+/// [`CHECKSUM_SIZE`] bytes after it.
+/// The total number of bytes is returned.
+pub(super) fn seal(buffer: &mut [u8], magic: &[u8; 3], version: u8, length: usize) -> usize {
+    buffer[..3].copy_from_slice(magic);
+    buffer[3] = version;
+    buffer[4..6].copy_from_slice(&(length as u16).to_le_bytes());
+
+    let end = HEADER_SIZE + length;
+    let checksum = fletcher16(&buffer[..end]);
+    buffer[end..end + CHECKSUM_SIZE].copy_from_slice(&checksum.to_le_bytes());
+    end + CHECKSUM_SIZE
+}
+
+/// Check the header and the checksum of some data, returning the version and
+/// the payload
+///
+/// Data filled with zeros is empty, and versions up to the latest one are
+/// accepted.
+pub(super) fn unseal<'a>(
+    data: &'a [u8],
+    magic: &[u8; 3],
+    latest: u8,
+) -> Result<(u8, &'a [u8]), LoadError> {
+    if data.iter().all(|byte| *byte == 0) {
+        return Err(LoadError::Empty);
+    }
+    if data.len() < HEADER_SIZE {
+        return Err(LoadError::Truncated);
+    }
+    if &data[..3] != magic {
+        return Err(LoadError::InvalidMagic);
+    }
+    let version = data[3];
+    if version == 0 || version > latest {
+        return Err(LoadError::UnsupportedVersion(version));
+    }
+
+    let length = u16::from_le_bytes([data[4], data[5]]) as usize;
+    let end = HEADER_SIZE + length;
+    if data.len() < end + CHECKSUM_SIZE {
+        return Err(LoadError::Truncated);
+    }
+    let checksum = u16::from_le_bytes([data[end], data[end + 1]]);
+    if checksum != fletcher16(&data[..end]) {
+        return Err(LoadError::Corrupted);
+    }
+    Ok((version, &data[HEADER_SIZE..end]))
+}
+
+/// Read the save in a slot of a disk image into a save
+///
+/// Slots past the end of the image are empty.
+pub fn read_slot(image: &[u8], slot: usize, save: &mut Save) -> Result<(), LoadError> {
+    let start = (slot * Save::MAX_SIZE).min(image.len());
+    let end = (start + Save::MAX_SIZE).min(image.len());
+    save.decode_into(&image[start..end])
+}
+
+/// Write a save into a slot of a disk image
+///
+/// The image must be at least [`SLOTS_SIZE`] bytes long.
+pub fn write_slot(image: &mut [u8], slot: usize, save: &Save) {
+    let mut buffer = [0; Save::MAX_SIZE];
+    save.encode(&mut buffer);
+    image[slot * Save::MAX_SIZE..(slot + 1) * Save::MAX_SIZE].copy_from_slice(&buffer);
+}
+
+/// Empty a slot of a disk image
+pub fn clear_slot(image: &mut [u8], slot: usize) {
+    for byte in image[slot * Save::MAX_SIZE..(slot + 1) * Save::MAX_SIZE].iter_mut() {
+        *byte = 0;
+    }
+}
+
+pub(super) fn flag(value: bool, mask: u8) -> u8 {
+    if value {
+        mask
+    } else {
+        0
+    }
+}
+
+/// Compute the Fletcher-16 checksum of some bytes
+fn fletcher16(bytes: &[u8]) -> u16 {
+    let (first, second) = bytes.iter().fold((0u16, 0u16), |(first, second), byte| {
+        let first = (first + *byte as u16) % 255;
+        (first, (second + first) % 255)
+    });
+    second << 8 | first
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn save() -> Save {
+        let mut world = World::new();
+        world.set_cell(8, 6, true);
+        world.set_cell(9, 7, true);
+        world.set_cell(7, 8, true);
+        world.set_cell(8, 8, true);
+        world.set_cell(9, 8, true);
+        world.set_cell(37, 17, true);
+
+        Save {
+            world,
+            rule: Rule::parse("B36/S23").unwrap(),
+            generation: 1234,
+            speed: 3,
+            paused: true,
+            auto_pause: false,
+            heat_map: true,
+            statistics_panel: false,
+            preview: true,
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let expected = save();
+        let mut buffer = [0; Save::MAX_SIZE];
+
+        let size = expected.encode(&mut buffer);
+        let actual = Save::decode(&buffer[..size]);
+
+        assert_eq!(actual, Ok(expected));
+    }
+
+    #[test]
+    fn compact() {
+        let mut buffer = [0; Save::MAX_SIZE];
+
+        let size = save().encode(&mut buffer);
+
+        assert!(size < 48);
+    }
+
+    #[test]
+    fn version_1() {
+        let expected = save();
+        let mut buffer = [0; HEADER_SIZE + STATE_SIZE + PACKED_SIZE + CHECKSUM_SIZE];
+        buffer[..4].copy_from_slice(b"GoL\x01");
+        buffer[4..6].copy_from_slice(&((STATE_SIZE + PACKED_SIZE) as u16).to_le_bytes());
+        buffer[6..10].copy_from_slice(&expected.rule.index().to_le_bytes());
+        buffer[10..14].copy_from_slice(&expected.generation.to_le_bytes());
+        buffer[14] = expected.speed;
+        buffer[15] = PAUSED | HEAT_MAP | PREVIEW;
+        for y in 0..World::HEIGHT {
+            for x in 0..World::WIDTH {
+                if expected.world.get_cell(x as i16, y as i16) {
+                    let i = y * World::WIDTH + x;
+                    buffer[16 + i / 8] |= 1 << (i % 8);
+                }
+            }
+        }
+        let end = buffer.len() - CHECKSUM_SIZE;
+        let checksum = fletcher16(&buffer[..end]);
+        buffer[end..].copy_from_slice(&checksum.to_le_bytes());
+
+        let actual = Save::decode(&buffer);
+
+        assert_eq!(actual, Ok(expected));
+    }
+
+    #[test]
+    fn empty() {
+        let actual = Save::decode(&[]);
+
+        assert_eq!(actual, Err(LoadError::Empty));
+    }
+
+    #[test]
+    fn invalid_header() {
+        let mut buffer = [0; Save::MAX_SIZE];
+        let size = save().encode(&mut buffer);
+
+        let mut invalid = buffer;
+        invalid[0] = b'X';
+        assert_eq!(Save::decode(&invalid), Err(LoadError::InvalidMagic));
+
+        let mut old = buffer;
+        old[3] = 0;
+        assert_eq!(Save::decode(&old), Err(LoadError::UnsupportedVersion(0)));
+
+        let mut new = buffer;
+        new[3] = VERSION + 1;
+        assert_eq!(
+            Save::decode(&new),
+            Err(LoadError::UnsupportedVersion(VERSION + 1))
+        );
+
+        assert_eq!(Save::decode(&buffer[..4]), Err(LoadError::Truncated));
+        assert_eq!(Save::decode(&buffer[..size - 1]), Err(LoadError::Truncated));
+    }
+
+    #[test]
+    fn corrupted() {
+        let mut buffer = [0; Save::MAX_SIZE];
+        save().encode(&mut buffer);
+
+        buffer[HEADER_SIZE + 5] ^= 0x10;
+        let actual = Save::decode(&buffer);
+
+        assert_eq!(actual, Err(LoadError::Corrupted));
+    }
+
+    #[test]
+    fn replace() {
+        let expected = save();
+        let mut actual = Save::EMPTY;
+
+        actual.replace(&expected);
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn slots() {
+        let mut image = [0; SLOTS_SIZE];
+        let expected = save();
+
+        write_slot(&mut image, 0, &expected);
+        write_slot(&mut image, 3, &expected);
+        clear_slot(&mut image, 0);
+
+        let mut actual = Save::EMPTY;
+        assert_eq!(read_slot(&image, 0, &mut actual), Err(LoadError::Empty));
+        assert_eq!(read_slot(&image, 3, &mut actual), Ok(()));
+        assert_eq!(actual, expected);
+        assert_eq!(
+            read_slot(&image[..100], 4, &mut actual),
+            Err(LoadError::Empty)
+        );
+    }
+
+    #[test]
+    fn checksum() {
+        let actual = fletcher16(b"abcde");
+
+        assert_eq!(actual, 0xc8f0);
+    }
+}
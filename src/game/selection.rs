@@ -0,0 +1,162 @@
+// Copyright Claudio Mattera 2021.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Selections of cells
+
+use super::{Pattern, Region, World};
+
+/// A rectangular selection of cells of the world
+///
+/// Like regions, selections wrap around the edges of the world.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Selection {
+    region: Region,
+}
+
+impl Selection {
+    pub const fn new(region: Region) -> Self {
+        Self { region }
+    }
+
+    pub fn region(&self) -> Region {
+        self.region
+    }
+
+    /// Extract the selected cells as a pattern
+    pub fn copy(&self, world: &World) -> Pattern {
+        world.extract_pattern(self.region)
+    }
+
+    /// Extract the selected cells as a pattern and clear them
+    pub fn cut(&self, world: &mut World) -> Pattern {
+        let pattern = self.copy(world);
+        self.clear(world);
+        pattern
+    }
+
+    /// Make all selected cells dead
+    pub fn clear(&self, world: &mut World) {
+        for (x, y) in self.region.cells() {
+            world.set_cell(x, y, false);
+        }
+    }
+
+    /// Make all selected cells alive
+    pub fn fill(&self, world: &mut World) {
+        for (x, y) in self.region.cells() {
+            world.set_cell(x, y, true);
+        }
+    }
+
+    /// Flip all selected cells
+    pub fn invert(&self, world: &mut World) {
+        for (x, y) in self.region.cells() {
+            world.flip_cell(x, y);
+        }
+    }
+
+    /// Rotate the selected cells by 90 degrees clockwise around the top-left
+    /// corner of the selection, which then covers the rotated cells
+    ///
+    /// Nothing happens when the rotated selection would not fit in the world.
+    pub fn rotate(&mut self, world: &mut World) {
+        if self.region.height > World::WIDTH || self.region.width > World::HEIGHT {
+            return;
+        }
+        self.transform(world, Pattern::rotated);
+    }
+
+    /// Reflect the selected cells around the vertical axis of the selection
+    pub fn reflect_horizontally(&mut self, world: &mut World) {
+        self.transform(world, Pattern::reflected_horizontally);
+    }
+
+    /// Reflect the selected cells around the horizontal axis of the selection
+    pub fn reflect_vertically(&mut self, world: &mut World) {
+        self.transform(world, Pattern::reflected_vertically);
+    }
+
+    fn transform(&mut self, world: &mut World, function: fn(&Pattern) -> Pattern) {
+        let pattern = function(&self.cut(world));
+        self.region = Region::new(
+            self.region.x,
+            self.region.y,
+            pattern.width(),
+            pattern.height(),
+        );
+        world.place_pattern(&pattern, self.region.x, self.region.y);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn world(cells: &[(i16, i16)]) -> World {
+        let mut world = World::new();
+        for (x, y) in cells.iter() {
+            world.set_cell(*x, *y, true);
+        }
+        world
+    }
+
+    #[test]
+    fn cut_and_paste() {
+        let mut actual = world(&[(2, 2), (3, 2), (3, 3), (10, 10)]);
+        let selection = Selection::new(Region::new(2, 2, 2, 2));
+
+        let pattern = selection.cut(&mut actual);
+        actual.place_pattern(&pattern, 20, 5);
+
+        let expected = world(&[(20, 5), (21, 5), (21, 6), (10, 10)]);
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn clear_fill_and_invert() {
+        let selection = Selection::new(Region::new(World::WIDTH as i16 - 1, 0, 2, 1));
+        let mut actual = world(&[(0, 0), (5, 5)]);
+
+        selection.invert(&mut actual);
+        assert_eq!(actual, world(&[(World::WIDTH as i16 - 1, 0), (5, 5)]));
+
+        selection.fill(&mut actual);
+        assert_eq!(
+            actual,
+            world(&[(World::WIDTH as i16 - 1, 0), (0, 0), (5, 5)])
+        );
+
+        selection.clear(&mut actual);
+        assert_eq!(actual, world(&[(5, 5)]));
+    }
+
+    #[test]
+    fn transformations() {
+        let mut selection = Selection::new(Region::new(4, 4, 3, 2));
+        let mut actual = world(&[(4, 4), (5, 4), (6, 4), (4, 5)]);
+
+        selection.rotate(&mut actual);
+        assert_eq!(selection.region(), Region::new(4, 4, 2, 3));
+        assert_eq!(actual, world(&[(4, 4), (5, 4), (5, 5), (5, 6)]));
+
+        selection.reflect_horizontally(&mut actual);
+        assert_eq!(actual, world(&[(4, 4), (5, 4), (4, 5), (4, 6)]));
+
+        selection.reflect_vertically(&mut actual);
+        assert_eq!(actual, world(&[(4, 4), (4, 5), (4, 6), (5, 6)]));
+    }
+
+    #[test]
+    fn rotation_too_large() {
+        let mut selection = Selection::new(Region::new(0, 0, World::WIDTH, 1));
+        let mut actual = world(&[(0, 0)]);
+
+        selection.rotate(&mut actual);
+
+        assert_eq!(selection.region(), Region::new(0, 0, World::WIDTH, 1));
+        assert_eq!(actual, world(&[(0, 0)]));
+    }
+}
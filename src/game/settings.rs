@@ -0,0 +1,184 @@
+// Copyright Claudio Mattera 2021.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! User settings
+//!
+//! Settings are stored on the disk after the save slots, framed with the same
+//! header and checksum as saves but with magic bytes "GoS".
+//!
+//! The payload contains the palette index, the speed, the rule index as a
+//! little-endian 32-bit number, the topology and a byte of flags.
+
+use crate::graphics::Palette;
+
+use super::save::{flag, seal, unseal, CHECKSUM_SIZE, HEADER_SIZE};
+use super::{LoadError, Rule, Topology, SLOTS_SIZE};
+
+const MAGIC: &[u8; 3] = b"GoS";
+const VERSION: u8 = 1;
+const PAYLOAD_SIZE: usize = 1 + 1 + 4 + 1 + 1;
+
+const HEAT_MAP: u8 = 1 << 0;
+const STATISTICS_PANEL: u8 = 1 << 1;
+const PREVIEW: u8 = 1 << 2;
+const AUTO_PAUSE: u8 = 1 << 3;
+
+/// Offset of the settings on the disk
+pub const SETTINGS_OFFSET: usize = SLOTS_SIZE;
+
+/// Options of the game to remember across sessions
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Settings {
+    pub palette: Palette,
+    pub speed: u8,
+    pub rule: Rule,
+    pub topology: Topology,
+    pub heat_map: bool,
+    pub statistics_panel: bool,
+    pub preview: bool,
+    pub auto_pause: bool,
+}
+
+impl Settings {
+    /// Settings used when none were stored, or after a reset
+    pub const DEFAULT: Settings = Settings {
+        palette: Palette::IceCream,
+        speed: 1,
+        rule: Rule::CONWAY,
+        topology: Topology::Torus,
+        heat_map: false,
+        statistics_panel: false,
+        preview: false,
+        auto_pause: false,
+    };
+
+    /// Maximal number of bytes of encoded settings
+    pub const MAX_SIZE: usize = HEADER_SIZE + PAYLOAD_SIZE + CHECKSUM_SIZE;
+
+    /// Encode the settings into a buffer, returning the number of bytes used
+    pub fn encode(&self, buffer: &mut [u8; Settings::MAX_SIZE]) -> usize {
+        let payload = &mut buffer[HEADER_SIZE..HEADER_SIZE + PAYLOAD_SIZE];
+        payload[0] = self.palette.index() as u8;
+        payload[1] = self.speed;
+        payload[2..6].copy_from_slice(&self.rule.index().to_le_bytes());
+        payload[6] = match self.topology {
+            Topology::Torus => 0,
+            Topology::Plane => 1,
+        };
+        payload[7] = flag(self.heat_map, HEAT_MAP)
+            | flag(self.statistics_panel, STATISTICS_PANEL)
+            | flag(self.preview, PREVIEW)
+            | flag(self.auto_pause, AUTO_PAUSE);
+
+        seal(buffer, MAGIC, VERSION, PAYLOAD_SIZE)
+    }
+
+    /// Decode settings, checking their header, checksum and values
+    pub fn decode(data: &[u8]) -> Result<Self, LoadError> {
+        let (_, payload) = unseal(data, MAGIC, VERSION)?;
+        if payload.len() < PAYLOAD_SIZE {
+            return Err(LoadError::Corrupted);
+        }
+
+        let palette = Palette::from_index(payload[0] as usize).ok_or(LoadError::Corrupted)?;
+        let speed = payload[1];
+        if !(1..=6).contains(&speed) {
+            return Err(LoadError::Corrupted);
+        }
+        let index = u32::from_le_bytes([payload[2], payload[3], payload[4], payload[5]]);
+        if index >= Rule::COUNT {
+            return Err(LoadError::Corrupted);
+        }
+        let topology = match payload[6] {
+            0 => Topology::Torus,
+            1 => Topology::Plane,
+            _ => return Err(LoadError::Corrupted),
+        };
+        let flags = payload[7];
+
+        Ok(Self {
+            palette,
+            speed,
+            rule: Rule::from_index(index),
+            topology,
+            heat_map: flags & HEAT_MAP != 0,
+            statistics_panel: flags & STATISTICS_PANEL != 0,
+            preview: flags & PREVIEW != 0,
+            auto_pause: flags & AUTO_PAUSE != 0,
+        })
+    }
+
+    /// Read the settings from a disk image
+    pub fn read(image: &[u8]) -> Result<Self, LoadError> {
+        let end = (SETTINGS_OFFSET + Settings::MAX_SIZE).min(image.len());
+        Self::decode(image.get(SETTINGS_OFFSET..end).unwrap_or(&[]))
+    }
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let settings = Settings {
+            palette: Palette::Rustic,
+            speed: 4,
+            rule: Rule::parse("B36/S23").unwrap(),
+            topology: Topology::Plane,
+            heat_map: true,
+            statistics_panel: false,
+            preview: true,
+            auto_pause: true,
+        };
+        let mut buffer = [0; Settings::MAX_SIZE];
+
+        let size = settings.encode(&mut buffer);
+        let actual = Settings::decode(&buffer[..size]);
+
+        assert_eq!(size, Settings::MAX_SIZE);
+        assert_eq!(actual, Ok(settings));
+    }
+
+    #[test]
+    fn defaults() {
+        let image = [0; crate::storage::Disk::SIZE];
+
+        let actual = Settings::read(&image);
+
+        assert_eq!(actual, Err(LoadError::Empty));
+        assert_eq!(Settings::default().palette, Palette::IceCream);
+        assert_eq!(Settings::default().rule, Rule::CONWAY);
+    }
+
+    #[test]
+    fn invalid_settings() {
+        let mut buffer = [0; Settings::MAX_SIZE];
+        Settings::DEFAULT.encode(&mut buffer);
+
+        let mut version = buffer;
+        version[3] = VERSION + 1;
+        assert_eq!(
+            Settings::decode(&version),
+            Err(LoadError::UnsupportedVersion(VERSION + 1))
+        );
+
+        let mut corrupted = buffer;
+        corrupted[HEADER_SIZE] ^= 0xff;
+        assert_eq!(Settings::decode(&corrupted), Err(LoadError::Corrupted));
+
+        let mut speed = buffer;
+        speed[HEADER_SIZE + 1] = 9;
+        seal(&mut speed, MAGIC, VERSION, PAYLOAD_SIZE);
+        assert_eq!(Settings::decode(&speed), Err(LoadError::Corrupted));
+    }
+}
@@ -0,0 +1,144 @@
+// Copyright Claudio Mattera 2021.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Pattern stamps
+
+use super::{Pattern, World};
+
+/// A pattern to place in the world, centred on a cell
+///
+/// The pattern can be rotated and reflected before placing it, and it wraps
+/// around the edges of the world.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Stamp {
+    pattern: Pattern,
+}
+
+impl Stamp {
+    pub fn new(pattern: Pattern) -> Self {
+        Self { pattern }
+    }
+
+    pub fn pattern(&self) -> &Pattern {
+        &self.pattern
+    }
+
+    /// Rotate the stamp by 90 degrees clockwise
+    pub fn rotate(&mut self) {
+        self.pattern = self.pattern.rotated();
+    }
+
+    /// Reflect the stamp around its vertical axis
+    pub fn reflect_horizontally(&mut self) {
+        self.pattern = self.pattern.reflected_horizontally();
+    }
+
+    /// Reflect the stamp around its horizontal axis
+    pub fn reflect_vertically(&mut self) {
+        self.pattern = self.pattern.reflected_vertically();
+    }
+
+    /// Find the cell of the world under the top-left corner of the stamp when
+    /// centred on a cell
+    pub fn origin(&self, x: i16, y: i16) -> (i16, i16) {
+        (
+            x - (self.pattern.width() / 2) as i16,
+            y - (self.pattern.height() / 2) as i16,
+        )
+    }
+
+    /// Iterate over the cells of the world made alive by the stamp when
+    /// centred on a cell
+    pub fn cells(&self, x: i16, y: i16) -> impl Iterator<Item = (i16, i16)> + '_ {
+        let (x0, y0) = self.origin(x, y);
+        self.pattern.live_cells().map(move |(dx, dy)| {
+            (
+                (x0 + dx as i16).rem_euclid(World::WIDTH as i16),
+                (y0 + dy as i16).rem_euclid(World::HEIGHT as i16),
+            )
+        })
+    }
+
+    /// Place the stamp in the world, centred on a cell
+    pub fn place(&self, world: &mut World, x: i16, y: i16) {
+        let (x0, y0) = self.origin(x, y);
+        world.place_pattern(&self.pattern, x0, y0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn glider() -> Stamp {
+        let mut pattern = Pattern::new(3, 3);
+        pattern.set_cell(1, 0, true);
+        pattern.set_cell(2, 1, true);
+        pattern.set_cell(0, 2, true);
+        pattern.set_cell(1, 2, true);
+        pattern.set_cell(2, 2, true);
+        Stamp::new(pattern)
+    }
+
+    #[test]
+    fn place_in_the_middle() {
+        let mut world = World::new();
+
+        glider().place(&mut world, 10, 10);
+
+        let mut expected = World::new();
+        expected.set_cell(10, 9, true);
+        expected.set_cell(11, 10, true);
+        expected.set_cell(9, 11, true);
+        expected.set_cell(10, 11, true);
+        expected.set_cell(11, 11, true);
+        assert_eq!(world, expected);
+    }
+
+    #[test]
+    fn place_across_the_corner() {
+        let stamp = glider();
+        let mut world = World::new();
+
+        stamp.place(&mut world, 0, 0);
+
+        let last_x = World::WIDTH as i16 - 1;
+        let last_y = World::HEIGHT as i16 - 1;
+        let mut expected = World::new();
+        expected.set_cell(0, last_y, true);
+        expected.set_cell(1, 0, true);
+        expected.set_cell(last_x, 1, true);
+        expected.set_cell(0, 1, true);
+        expected.set_cell(1, 1, true);
+        assert_eq!(world, expected);
+
+        let mut cells = World::new();
+        for (x, y) in stamp.cells(0, 0) {
+            cells.set_cell(x, y, true);
+        }
+        assert_eq!(cells, expected);
+    }
+
+    #[test]
+    fn transformations() {
+        let mut stamp = glider();
+
+        stamp.rotate();
+        stamp.rotate();
+        stamp.rotate();
+        stamp.rotate();
+        assert_eq!(stamp, glider());
+
+        stamp.reflect_horizontally();
+        let actual: Vec<_> = stamp.pattern().live_cells().collect();
+        assert_eq!(actual, vec![(1, 0), (0, 1), (0, 2), (1, 2), (2, 2)]);
+
+        stamp.reflect_horizontally();
+        stamp.reflect_vertically();
+        let actual: Vec<_> = stamp.pattern().live_cells().collect();
+        assert_eq!(actual, vec![(0, 0), (1, 0), (2, 0), (2, 1), (1, 2)]);
+    }
+}
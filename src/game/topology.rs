@@ -0,0 +1,28 @@
+// Copyright Claudio Mattera 2021.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! World topologies
+
+/// The shape of the world, i.e. what lies past its edges
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Topology {
+    /// Opposite edges are joined, so patterns leaving the world on one side
+    /// enter it on the other side
+    Torus,
+
+    /// Cells past the edges are always dead
+    Plane,
+}
+
+impl Topology {
+    /// Get the name of the topology
+    pub fn name(&self) -> &'static str {
+        match self {
+            Topology::Torus => "Torus",
+            Topology::Plane => "Plane",
+        }
+    }
+}
@@ -6,7 +6,9 @@
 
 //! World structure
 
-#[derive(Debug)]
+use super::{Pattern, Region, Rule, Topology};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct World([bool; World::WIDTH * World::HEIGHT]);
 
 impl World {
@@ -39,12 +41,140 @@ impl World {
         self.0[y * Self::WIDTH + x]
     }
 
+    pub fn population(&self) -> usize {
+        self.0.iter().filter(|alive| **alive).count()
+    }
+
+    /// Compute a 64-bit FNV-1a hash of the cells
+    ///
+    /// Equal worlds always have equal hashes, so the hash can be used to
+    /// cheaply detect repeated generations.
+    pub fn hash(&self) -> u64 {
+        fnv1a(self.0.chunks(8).map(|chunk| {
+            chunk
+                .iter()
+                .enumerate()
+                .fold(0u8, |byte, (i, alive)| byte | ((*alive as u8) << i))
+        }))
+    }
+
+    /// Compute the smallest region containing all live cells
+    ///
+    /// Since the world wraps around its edges, the region can extend past
+    /// the right and bottom edges.
+    /// An empty world has no bounding box.
+    pub fn bounding_box(&self) -> Option<Region> {
+        let mut columns = [false; Self::WIDTH];
+        let mut rows = [false; Self::HEIGHT];
+        for (y, row) in rows.iter_mut().enumerate() {
+            for (x, column) in columns.iter_mut().enumerate() {
+                if self.get_cell(x as i16, y as i16) {
+                    *column = true;
+                    *row = true;
+                }
+            }
+        }
+
+        let (x, width) = circular_span(&columns)?;
+        let (y, height) = circular_span(&rows)?;
+        Some(Region::new(x as i16, y as i16, width, height))
+    }
+
+    /// Extract the cells in a region as a pattern
+    pub fn extract_pattern(&self, region: Region) -> Pattern {
+        let mut pattern = Pattern::new(region.width, region.height);
+        for (i, (x, y)) in region.cells().enumerate() {
+            pattern.set_cell(i % region.width, i / region.width, self.get_cell(x, y));
+        }
+        pattern
+    }
+
+    /// Extract all live cells as a pattern normalised to their bounding box
+    pub fn pattern(&self) -> Pattern {
+        match self.bounding_box() {
+            Some(region) => self.extract_pattern(region),
+            None => Pattern::new(0, 0),
+        }
+    }
+
+    /// Create a world with a pattern in its centre
+    pub fn from_pattern(pattern: &Pattern) -> Self {
+        let mut world = Self::new();
+        let x = (Self::WIDTH.saturating_sub(pattern.width()) / 2) as i16;
+        let y = (Self::HEIGHT.saturating_sub(pattern.height()) / 2) as i16;
+        world.place_pattern(pattern, x, y);
+        world
+    }
+
+    /// Make the live cells of a pattern alive, with the top-left corner of
+    /// the pattern at the given cell
+    ///
+    /// The pattern wraps around the edges of the world.
+    pub fn place_pattern(&mut self, pattern: &Pattern, x: i16, y: i16) {
+        for (dx, dy) in pattern.live_cells() {
+            self.set_cell(
+                (x + dx as i16).rem_euclid(Self::WIDTH as i16),
+                (y + dy as i16).rem_euclid(Self::HEIGHT as i16),
+                true,
+            );
+        }
+    }
+
+    /// Compute a hash of the live cells invariant under translations,
+    /// rotations and reflections
+    pub fn canonical_hash(&self) -> u64 {
+        self.pattern().canonical_hash()
+    }
+
+    /// Advance the world by one generation
+    pub fn step(&mut self) {
+        self.step_with(Rule::CONWAY);
+    }
+
+    /// Advance the world by one generation according to a rule
+    pub fn step_with(&mut self, rule: Rule) {
+        self.step_in(rule, Topology::Torus);
+    }
+
+    /// Advance the world by one generation according to a rule in a topology
+    pub fn step_in(&mut self, rule: Rule, topology: Topology) {
+        let mut new_world = World::new();
+
+        for x in 0..(World::WIDTH as i16) {
+            for y in 0..(World::HEIGHT as i16) {
+                let alive = self.get_cell(x, y);
+                let count = match topology {
+                    Topology::Torus => self.count_live_neighbours(x, y),
+                    Topology::Plane => self.count_live_neighbours_in_plane(x, y),
+                };
+                new_world.set_cell(x, y, rule.next(alive, count));
+            }
+        }
+
+        self.replace(&new_world);
+    }
+
     pub fn count_live_neighbours(&self, x: i16, y: i16) -> usize {
         IntoIterator::into_iter(self.neighbours(x, y))
             .filter(|(x, y)| self.get_cell(*x, *y))
             .count()
     }
 
+    /// Count the live neighbours of a cell, ignoring those past the edges
+    fn count_live_neighbours_in_plane(&self, x: i16, y: i16) -> usize {
+        let width = Self::WIDTH as i16;
+        let height = Self::HEIGHT as i16;
+        let mut count = 0;
+        for ny in (y - 1).max(0)..=(y + 1).min(height - 1) {
+            for nx in (x - 1).max(0)..=(x + 1).min(width - 1) {
+                if (nx, ny) != (x, y) && self.get_cell(nx, ny) {
+                    count += 1;
+                }
+            }
+        }
+        count
+    }
+
     fn neighbours(&self, x: i16, y: i16) -> [(i16, i16); 8] {
         let width = Self::WIDTH as i16;
         let height = Self::HEIGHT as i16;
@@ -61,6 +191,46 @@ impl World {
     }
 }
 
+/// Compute the 64-bit FNV-1a hash of a sequence of bytes
+pub(super) fn fnv1a<I>(bytes: I) -> u64
+where
+    I: IntoIterator<Item = u8>,
+{
+    bytes.into_iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// Find the shortest span covering all occupied positions on a circular axis
+///
+/// The span is returned as its start and length, or `None` if no position is
+/// occupied.
+fn circular_span(occupied: &[bool]) -> Option<(usize, usize)> {
+    let size = occupied.len();
+    let mut longest_gap = 0;
+    let mut longest_gap_end = 0;
+    let mut gap = 0;
+    for i in 0..(2 * size) {
+        if occupied[i % size] {
+            gap = 0;
+        } else {
+            gap += 1;
+            if gap > longest_gap && gap <= size {
+                longest_gap = gap;
+                longest_gap_end = i % size;
+            }
+        }
+    }
+
+    if longest_gap == size {
+        None
+    } else if longest_gap == 0 {
+        Some((0, size))
+    } else {
+        Some(((longest_gap_end + 1) % size, size - longest_gap))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -188,4 +358,173 @@ mod tests {
         assert_eq!(world.count_live_neighbours(9, 8), 3);
         assert_eq!(world.count_live_neighbours(9, 9), 2);
     }
+
+    #[test]
+    fn population() {
+        let mut world = World::new();
+        world.set_cell(8, 7, true);
+        world.set_cell(8, 8, true);
+        world.set_cell(8, 9, true);
+
+        assert_eq!(world.population(), 3);
+    }
+
+    #[test]
+    fn hash_of_equal_worlds() {
+        let mut world = World::new();
+        world.set_cell(8, 7, true);
+        let mut other = World::new();
+        other.set_cell(8, 7, true);
+
+        assert_eq!(world.hash(), other.hash());
+
+        other.set_cell(8, 8, true);
+
+        assert_ne!(world.hash(), other.hash());
+    }
+
+    #[test]
+    fn place_pattern_across_the_edges() {
+        let mut pattern = Pattern::new(2, 1);
+        pattern.set_cell(0, 0, true);
+        pattern.set_cell(1, 0, true);
+
+        let mut world = World::new();
+        world.place_pattern(&pattern, World::WIDTH as i16 - 1, 3);
+
+        assert!(world.get_cell(World::WIDTH as i16 - 1, 3));
+        assert!(world.get_cell(0, 3));
+        assert_eq!(world.population(), 2);
+    }
+
+    #[test]
+    fn from_pattern_is_centred() {
+        let mut pattern = Pattern::new(2, 2);
+        pattern.set_cell(0, 0, true);
+
+        let world = World::from_pattern(&pattern);
+
+        let expected = Region::new(18, 8, 1, 1);
+        assert_eq!(world.bounding_box(), Some(expected));
+        assert_eq!(world.pattern().population(), 1);
+    }
+
+    #[test]
+    fn step_blinker() {
+        let mut world = World::new();
+        world.set_cell(8, 7, true);
+        world.set_cell(8, 8, true);
+        world.set_cell(8, 9, true);
+
+        world.step();
+
+        let mut expected = World::new();
+        expected.set_cell(7, 8, true);
+        expected.set_cell(8, 8, true);
+        expected.set_cell(9, 8, true);
+
+        assert_eq!(world, expected);
+    }
+
+    #[test]
+    fn step_with_rule() {
+        let mut world = World::new();
+        world.set_cell(8, 8, true);
+        world.set_cell(9, 8, true);
+
+        world.step_with(Rule::parse("B2/S").unwrap());
+
+        let mut expected = World::new();
+        for x in 8..10 {
+            expected.set_cell(x, 7, true);
+            expected.set_cell(x, 9, true);
+        }
+
+        assert_eq!(world, expected);
+    }
+
+    #[test]
+    fn bounding_box() {
+        let mut world = World::new();
+        world.set_cell(8, 6, true);
+        world.set_cell(9, 7, true);
+        world.set_cell(7, 8, true);
+
+        let actual = world.bounding_box();
+        let expected = Some(Region::new(7, 6, 3, 3));
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn bounding_box_across_the_edges() {
+        let mut world = World::new();
+        world.set_cell(0, 0, true);
+        world.set_cell((World::WIDTH as i16) - 2, (World::HEIGHT as i16) - 1, true);
+
+        let actual = world.bounding_box();
+        let expected = Some(Region::new(
+            (World::WIDTH as i16) - 2,
+            (World::HEIGHT as i16) - 1,
+            3,
+            2,
+        ));
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn bounding_box_of_empty_world() {
+        let world = World::new();
+        assert_eq!(world.bounding_box(), None);
+    }
+
+    #[test]
+    fn pattern_is_normalised() {
+        let mut world = World::new();
+        world.set_cell(0, 0, true);
+        world.set_cell((World::WIDTH as i16) - 1, 0, true);
+
+        let pattern = world.pattern();
+
+        assert_eq!((pattern.width(), pattern.height()), (2, 1));
+        assert_eq!(pattern.population(), 2);
+    }
+
+    #[test]
+    fn canonical_hash_is_invariant() {
+        let mut world = World::new();
+        world.set_cell(8, 6, true);
+        world.set_cell(9, 7, true);
+        world.set_cell(7, 8, true);
+        world.set_cell(8, 8, true);
+        world.set_cell(9, 8, true);
+
+        let mut other = World::new();
+        other.set_cell(20, 11, true);
+        other.set_cell(20, 12, true);
+        other.set_cell(20, 13, true);
+        other.set_cell(21, 11, true);
+        other.set_cell(22, 12, true);
+
+        assert_eq!(world.canonical_hash(), other.canonical_hash());
+        assert_ne!(world.hash(), other.hash());
+    }
+
+    #[test]
+    fn blinker_across_the_edge() {
+        let mut world = World::new();
+        world.set_cell(0, 5, true);
+        world.set_cell(1, 5, true);
+        world.set_cell((World::WIDTH as i16) - 1, 5, true);
+
+        let mut torus = world.clone();
+        torus.step_in(Rule::CONWAY, Topology::Torus);
+        let mut plane = world.clone();
+        plane.step_in(Rule::CONWAY, Topology::Plane);
+
+        assert_eq!(torus.population(), 3);
+        assert!(torus.get_cell(0, 4));
+        assert_eq!(plane.population(), 0);
+    }
 }
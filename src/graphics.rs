@@ -12,6 +12,9 @@ pub use drawcolors::set_drawing_colors;
 mod palette;
 pub use palette::Palette;
 
+#[cfg(any(feature = "std", test))]
+pub mod gif;
+
 use crate::wasm4::{hline, line, rect, text, vline};
 
 /// Draw a rectangle using the current colours
@@ -0,0 +1,371 @@
+// Copyright Claudio Mattera 2021.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Animated GIF export
+//!
+//! Worlds are rendered with the same geometry as on the screen: a 160×80
+//! frame with a border of four pixels, and cells of 4×4 pixels.
+//! The four colours of a [`Palette`] are the only colours of the image, so
+//! pixels are stored as indices in the palette and compressed with the LZW
+//! variant of the GIF format.
+
+use std::collections::HashMap;
+use std::io::{self, Write};
+
+use crate::game::{Rule, Topology, World};
+
+use super::Palette;
+
+/// Width of a rendered world, in pixels
+pub const FRAME_WIDTH: usize = 160;
+
+/// Height of a rendered world, in pixels
+pub const FRAME_HEIGHT: usize = 80;
+
+/// Size of the border around the cells, in pixels
+const BORDER: usize = 4;
+
+/// Size of a cell, in pixels
+const CELL_SIZE: usize = 4;
+
+/// Index of the colour of the background
+const BACKGROUND: u8 = 0;
+
+/// Index of the colour of live cells, the same as in `draw_world`
+const CELL: u8 = 1;
+
+/// Index of the colour of the border
+const FRAME: u8 = 3;
+
+/// Number of bits of the pixels, enough for the four colours of a palette
+const MIN_CODE_SIZE: u8 = 2;
+
+/// Maximal number of bits of LZW codes
+const MAX_CODE_SIZE: u8 = 12;
+
+/// Render a world as indices in a palette, row by row
+pub fn render_world(world: &World) -> Vec<u8> {
+    let mut pixels = vec![FRAME; FRAME_WIDTH * FRAME_HEIGHT];
+    for y in BORDER..FRAME_HEIGHT - BORDER {
+        for x in BORDER..FRAME_WIDTH - BORDER {
+            let cell_x = ((x - BORDER) / CELL_SIZE) as i16;
+            let cell_y = ((y - BORDER) / CELL_SIZE) as i16;
+            pixels[y * FRAME_WIDTH + x] = if world.get_cell(cell_x, cell_y) {
+                CELL
+            } else {
+                BACKGROUND
+            };
+        }
+    }
+    pixels
+}
+
+/// A writer of animated GIF images with the colours of a palette
+///
+/// The animation loops forever.
+pub struct GifWriter<W> {
+    out: W,
+    width: u16,
+    height: u16,
+}
+
+impl<W: Write> GifWriter<W> {
+    /// Start an image, writing its header
+    pub fn new(mut out: W, width: u16, height: u16, palette: &Palette) -> io::Result<Self> {
+        out.write_all(b"GIF89a")?;
+        out.write_all(&width.to_le_bytes())?;
+        out.write_all(&height.to_le_bytes())?;
+        // Global colour table of 2^(1 + 1) colours, with 2^(1 + 1) bits per
+        // primary colour
+        out.write_all(&[0x80 | (1 << 4) | 1, 0, 0])?;
+        for colour in palette.colours().iter() {
+            out.write_all(&colour.to_be_bytes()[1..])?;
+        }
+
+        out.write_all(&[0x21, 0xff, 11])?;
+        out.write_all(b"NETSCAPE2.0")?;
+        out.write_all(&[3, 1, 0, 0, 0])?;
+
+        Ok(Self { out, width, height })
+    }
+
+    /// Add a frame shown for a delay in hundredths of a second
+    ///
+    /// Pixels are indices in the palette, row by row.
+    pub fn write_frame(&mut self, pixels: &[u8], delay: u16) -> io::Result<()> {
+        if pixels.len() != self.width as usize * self.height as usize {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Frame does not match the size of the image",
+            ));
+        }
+
+        self.out.write_all(&[0x21, 0xf9, 4, 0])?;
+        self.out.write_all(&delay.to_le_bytes())?;
+        self.out.write_all(&[0, 0])?;
+
+        self.out.write_all(&[0x2c, 0, 0, 0, 0])?;
+        self.out.write_all(&self.width.to_le_bytes())?;
+        self.out.write_all(&self.height.to_le_bytes())?;
+        self.out.write_all(&[0])?;
+
+        self.out.write_all(&[MIN_CODE_SIZE])?;
+        for block in compress(pixels).chunks(255) {
+            self.out.write_all(&[block.len() as u8])?;
+            self.out.write_all(block)?;
+        }
+        self.out.write_all(&[0])
+    }
+
+    /// Finish the image, returning the underlying writer
+    pub fn finish(mut self) -> io::Result<W> {
+        self.out.write_all(&[0x3b])?;
+        Ok(self.out)
+    }
+}
+
+/// Write an animation of the evolution of a world
+///
+/// The first frame is the world itself, followed by the given number of
+/// generations.
+pub fn write_run<W: Write>(
+    out: W,
+    world: &World,
+    rule: Rule,
+    topology: Topology,
+    generations: usize,
+    palette: &Palette,
+    delay: u16,
+) -> io::Result<W> {
+    let mut writer = GifWriter::new(out, FRAME_WIDTH as u16, FRAME_HEIGHT as u16, palette)?;
+    let mut world = world.clone();
+    writer.write_frame(&render_world(&world), delay)?;
+    for _ in 0..generations {
+        world.step_in(rule, topology);
+        writer.write_frame(&render_world(&world), delay)?;
+    }
+    writer.finish()
+}
+
+/// Compress pixels with variable-length LZW codes, packed least significant
+/// bit first
+fn compress(pixels: &[u8]) -> Vec<u8> {
+    let clear = 1u16 << MIN_CODE_SIZE;
+    let end = clear + 1;
+
+    let mut bits = BitWriter::new();
+    let mut table: HashMap<(u16, u8), u16> = HashMap::new();
+    let mut code_size = MIN_CODE_SIZE + 1;
+    let mut next = end + 1;
+
+    bits.write(clear, code_size);
+    let mut pixels = pixels.iter();
+    let mut prefix = match pixels.next() {
+        Some(pixel) => *pixel as u16,
+        None => {
+            bits.write(end, code_size);
+            return bits.finish();
+        }
+    };
+    for pixel in pixels {
+        if let Some(code) = table.get(&(prefix, *pixel)) {
+            prefix = *code;
+            continue;
+        }
+
+        bits.write(prefix, code_size);
+        table.insert((prefix, *pixel), next);
+        next += 1;
+        if next > 1 << code_size && code_size < MAX_CODE_SIZE {
+            code_size += 1;
+        }
+        if next == 1 << MAX_CODE_SIZE {
+            bits.write(clear, code_size);
+            table.clear();
+            code_size = MIN_CODE_SIZE + 1;
+            next = end + 1;
+        }
+        prefix = *pixel as u16;
+    }
+    bits.write(prefix, code_size);
+    bits.write(end, code_size);
+    bits.finish()
+}
+
+/// A buffer of bits, filled from the least significant bit of each byte
+struct BitWriter {
+    bytes: Vec<u8>,
+    buffer: u32,
+    length: u8,
+}
+
+impl BitWriter {
+    fn new() -> Self {
+        Self {
+            bytes: Vec::new(),
+            buffer: 0,
+            length: 0,
+        }
+    }
+
+    fn write(&mut self, code: u16, size: u8) {
+        self.buffer |= (code as u32) << self.length;
+        self.length += size;
+        while self.length >= 8 {
+            self.bytes.push(self.buffer as u8);
+            self.buffer >>= 8;
+            self.length -= 8;
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.length > 0 {
+            self.bytes.push(self.buffer as u8);
+        }
+        self.bytes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::analysis::{soup, Random};
+
+    /// Decompress LZW codes as a GIF decoder does
+    fn decompress(data: &[u8]) -> Vec<u8> {
+        let clear = 1usize << MIN_CODE_SIZE;
+        let end = clear + 1;
+
+        let mut pixels = Vec::new();
+        let mut table: Vec<Vec<u8>> = Vec::new();
+        let mut code_size = MIN_CODE_SIZE + 1;
+        let mut previous: Option<Vec<u8>> = None;
+        let mut position = 0;
+        loop {
+            let mut code = 0;
+            for i in 0..code_size as usize {
+                let bit = data[(position + i) / 8] >> ((position + i) % 8) & 1;
+                code |= (bit as usize) << i;
+            }
+            position += code_size as usize;
+
+            if code == clear {
+                table = (0..clear).map(|i| vec![i as u8]).collect();
+                table.push(Vec::new());
+                table.push(Vec::new());
+                code_size = MIN_CODE_SIZE + 1;
+                previous = None;
+                continue;
+            }
+            if code == end {
+                return pixels;
+            }
+
+            let entry = match (&previous, table.get(code)) {
+                (_, Some(entry)) => entry.clone(),
+                (Some(previous), None) => {
+                    let mut entry = previous.clone();
+                    entry.push(previous[0]);
+                    entry
+                }
+                (None, None) => panic!("Invalid code {}", code),
+            };
+            if let Some(mut previous) = previous {
+                previous.push(entry[0]);
+                table.push(previous);
+            }
+            pixels.extend_from_slice(&entry);
+            previous = Some(entry);
+
+            if table.len() == 1 << code_size && code_size < MAX_CODE_SIZE {
+                code_size += 1;
+            }
+        }
+    }
+
+    #[test]
+    fn compress_round_trip() {
+        let mut random = Random::new(7);
+        let noise: Vec<u8> = (0..20000).map(|_| random.below(4) as u8).collect();
+        let inputs = [
+            vec![],
+            vec![2],
+            vec![0; 10000],
+            vec![0, 1, 0, 1, 0, 1, 0, 1, 2, 3, 2, 3, 2, 3],
+            noise,
+        ];
+        for input in inputs.iter() {
+            let actual = decompress(&compress(input));
+
+            assert_eq!(&actual, input);
+        }
+    }
+
+    #[test]
+    fn render_corners() {
+        let mut world = World::new();
+        world.set_cell(0, 0, true);
+        world.set_cell(World::WIDTH as i16 - 1, World::HEIGHT as i16 - 1, true);
+
+        let pixels = render_world(&world);
+
+        assert_eq!(pixels.len(), FRAME_WIDTH * FRAME_HEIGHT);
+        assert_eq!(pixels[0], FRAME);
+        assert_eq!(pixels[4 * FRAME_WIDTH + 4], CELL);
+        assert_eq!(pixels[7 * FRAME_WIDTH + 7], CELL);
+        assert_eq!(pixels[8 * FRAME_WIDTH + 8], BACKGROUND);
+        assert_eq!(pixels[75 * FRAME_WIDTH + 155], CELL);
+        assert_eq!(pixels[76 * FRAME_WIDTH + 156], FRAME);
+    }
+
+    #[test]
+    fn animation() {
+        let world = soup(3);
+
+        let data = write_run(
+            Vec::new(),
+            &world,
+            Rule::CONWAY,
+            Topology::Torus,
+            4,
+            &Palette::Gold,
+            10,
+        )
+        .unwrap();
+
+        assert_eq!(&data[..6], b"GIF89a");
+        assert_eq!(&data[6..10], &[160, 0, 80, 0]);
+        assert_eq!(&data[13..16], &[0xcf, 0xab, 0x51]);
+        assert_eq!(data.last(), Some(&0x3b));
+
+        let mut frames = Vec::new();
+        let mut i = 13 + 4 * 3 + 19;
+        while data[i] != 0x3b {
+            assert_eq!(&data[i..i + 3], &[0x21, 0xf9, 4]);
+            i += 8;
+            assert_eq!(data[i], 0x2c);
+            i += 10;
+            assert_eq!(data[i], MIN_CODE_SIZE);
+            i += 1;
+            let mut codes = Vec::new();
+            while data[i] != 0 {
+                let length = data[i] as usize;
+                codes.extend_from_slice(&data[i + 1..i + 1 + length]);
+                i += 1 + length;
+            }
+            i += 1;
+            frames.push(decompress(&codes));
+        }
+
+        let mut expected = world.clone();
+        assert_eq!(frames.len(), 5);
+        for frame in frames.iter() {
+            assert_eq!(frame, &render_world(&expected));
+            expected.step();
+        }
+    }
+}
@@ -15,6 +15,7 @@ use crate::wasm4::PALETTE;
 ///
 /// This enum defines several 4-colour palettes.
 // Generate colour placeholders at https://placeholderimage.dev/
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Palette {
     /// Default palette of WASM-4
     ///
@@ -74,9 +75,44 @@ pub enum Palette {
 }
 
 impl Palette {
-    /// Set the current palette
-    pub fn set(&self) {
-        let quadruple = match self {
+    /// All palettes, in order
+    pub const ALL: [Palette; 7] = [
+        Palette::Default,
+        Palette::Gold,
+        Palette::IceCream,
+        Palette::Hollow,
+        Palette::Wheat,
+        Palette::Rustic,
+        Palette::DustByte,
+    ];
+
+    /// Get a palette from its position in [`Palette::ALL`]
+    pub fn from_index(index: usize) -> Option<Self> {
+        Self::ALL.get(index).copied()
+    }
+
+    /// Get the position of the palette in [`Palette::ALL`]
+    pub fn index(&self) -> usize {
+        *self as usize
+    }
+
+    /// Get a short name of the palette
+    pub fn name(&self) -> &'static str {
+        match self {
+            Palette::Default => "Default",
+            Palette::Gold => "Gold",
+            Palette::IceCream => "Ice Cream",
+            Palette::Hollow => "Hollow",
+            Palette::Wheat => "Wheat",
+            Palette::Rustic => "Rustic",
+            Palette::DustByte => "DustByte",
+        }
+    }
+
+    /// Get the four colours of the palette as `0xRRGGBB`, from lightest to
+    /// darkest
+    pub fn colours(&self) -> [u32; 4] {
+        match self {
             Palette::Default => [0xdef7cd, 0x86bf6b, 0x306950, 0x071821],
             Palette::Gold => [0xcfab51, 0x9d654c, 0x4d222c, 0x210b1b],
             Palette::IceCream => [0xfff6d3, 0xf9a875, 0xeb6b6f, 0x7c3f58],
@@ -84,7 +120,11 @@ impl Palette {
             Palette::Wheat => [0xfffad6, 0xe6c12b, 0x7a3921, 0x240024],
             Palette::Rustic => [0xedb4a1, 0xa96868, 0x764462, 0x2c2137],
             Palette::DustByte => [0xf5e9bf, 0xaa644d, 0x788374, 0x372a39],
-        };
-        unsafe { *PALETTE = quadruple };
+        }
+    }
+
+    /// Set the current palette
+    pub fn set(&self) {
+        unsafe { *PALETTE = self.colours() };
     }
 }
@@ -23,6 +23,9 @@ pub use label::Label;
 mod spinbox;
 pub use spinbox::SpinBox;
 
+mod text;
+pub use text::TextBuffer;
+
 pub fn draw_title() {
     let first_line = "CONWAY'S";
     let second_line = "GAME OF LIFE";
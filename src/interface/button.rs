@@ -31,6 +31,15 @@ impl Button {
         self.is_mouse_over(mouse) && mouse.left_pressed()
     }
 
+    /// Check whether the button was released after being pressed
+    ///
+    /// Unlike [`Button::is_clicked`], this only happens once per click, so it
+    /// is suitable for actions that must not be repeated, such as switching
+    /// screens.
+    pub fn is_released(&self, mouse: &Mouse) -> bool {
+        self.is_mouse_over(mouse) && mouse.left_clicked()
+    }
+
     pub fn draw(&self, mouse: &Mouse) {
         if self.is_mouse_over(mouse) && mouse.left_pressed() {
             self.draw_pressed();
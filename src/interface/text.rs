@@ -0,0 +1,103 @@
+// Copyright Claudio Mattera 2021.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use core::str::from_utf8_unchecked;
+
+/// A fixed-capacity buffer to compose short texts
+///
+/// Formatting machinery from `core::fmt` is stripped from release builds,
+/// so dynamic texts are composed in this buffer instead.
+/// Text exceeding the capacity is silently truncated.
+#[derive(Debug)]
+pub struct TextBuffer<const N: usize> {
+    bytes: [u8; N],
+    length: usize,
+}
+
+impl<const N: usize> TextBuffer<N> {
+    pub const fn new() -> Self {
+        Self {
+            bytes: [0; N],
+            length: 0,
+        }
+    }
+
+    /// Append an ASCII string
+    pub fn push_str(&mut self, string: &str) -> &mut Self {
+        for byte in string.bytes().filter(u8::is_ascii) {
+            self.push_byte(byte);
+        }
+        self
+    }
+
+    /// Append a number in decimal notation
+    pub fn push_number(&mut self, value: u32) -> &mut Self {
+        let mut digits = [0u8; 10];
+        let mut count = 0;
+        let mut value = value;
+        loop {
+            digits[count] = b'0' + (value % 10) as u8;
+            count += 1;
+            value /= 10;
+            if value == 0 {
+                break;
+            }
+        }
+        for digit in digits[..count].iter().rev() {
+            self.push_byte(*digit);
+        }
+        self
+    }
+
+    pub fn as_str(&self) -> &str {
+        unsafe { from_utf8_unchecked(&self.bytes[..self.length]) }
+    }
+
+    fn push_byte(&mut self, byte: u8) {
+        if self.length < N {
+            self.bytes[self.length] = byte;
+            self.length += 1;
+        }
+    }
+}
+
+impl<const N: usize> Default for TextBuffer<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TextBuffer;
+
+    #[test]
+    fn push_str_and_number() {
+        let mut buffer = TextBuffer::<16>::new();
+        buffer.push_str("Period ").push_number(12);
+        let actual = buffer.as_str();
+        let expected = "Period 12";
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn push_zero() {
+        let mut buffer = TextBuffer::<16>::new();
+        buffer.push_number(0);
+        let actual = buffer.as_str();
+        let expected = "0";
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn truncate_when_full() {
+        let mut buffer = TextBuffer::<4>::new();
+        buffer.push_str("Gen ").push_number(1234);
+        let actual = buffer.as_str();
+        let expected = "Gen ";
+        assert_eq!(actual, expected);
+    }
+}
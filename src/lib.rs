@@ -7,25 +7,29 @@
 //! A game engine library implementing Conway's Game of Life based on WASM-4
 //! engine
 
-#![no_std]
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
 
+pub mod analysis;
+pub mod format;
 pub mod game;
 pub mod graphics;
 pub mod input;
 pub mod interface;
+pub mod library;
+pub mod storage;
 pub mod time;
 pub mod wasm4;
 
-#[cfg(target_family = "wasm")]
+#[cfg(all(target_family = "wasm", not(feature = "std")))]
 use core::arch::wasm32;
 
-#[cfg(target_family = "wasm")]
+#[cfg(all(target_family = "wasm", not(feature = "std")))]
 use core::panic::PanicInfo;
 
-#[cfg(target_family = "wasm")]
+#[cfg(all(target_family = "wasm", not(feature = "std")))]
 use crate::wasm4::trace;
 
-#[cfg(target_family = "wasm")]
+#[cfg(all(target_family = "wasm", not(feature = "std")))]
 #[panic_handler]
 fn panic_handler(_panic_info: &PanicInfo<'_>) -> ! {
     trace("panic error");
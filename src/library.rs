@@ -0,0 +1,156 @@
+// Copyright Claudio Mattera 2021.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Library of classic patterns
+//!
+//! Patterns are written in RLE format and parsed at compile time.
+
+use crate::format::embed;
+use crate::game::Pattern;
+
+/// A pattern of the library
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Entry {
+    pub name: &'static str,
+
+    /// Encoded cells, in RLE format
+    pub rle: &'static str,
+
+    pattern: Pattern,
+}
+
+impl Entry {
+    const fn new(name: &'static str, rle: &'static str) -> Self {
+        Self {
+            name,
+            rle,
+            pattern: embed::rle(rle),
+        }
+    }
+
+    pub fn pattern(&self) -> &Pattern {
+        &self.pattern
+    }
+}
+
+/// All patterns of the library
+pub static LIBRARY: [Entry; 10] = [
+    Entry::new("Glider", "x = 3, y = 3\nbo$2bo$3o!"),
+    Entry::new("LWSS", "x = 5, y = 4\nbo2bo$o$o3bo$4o!"),
+    Entry::new("MWSS", "x = 6, y = 5\n3bo$bo3bo$o$o4bo$5o!"),
+    Entry::new("HWSS", "x = 7, y = 5\n3b2o$bo4bo$o$o5bo$6o!"),
+    Entry::new(
+        "Pulsar",
+        "x = 13, y = 13\n\
+         2b3o3b3o2$o4bobo4bo$o4bobo4bo$o4bobo4bo$2b3o3b3o2$2b3o3b3o$\
+         o4bobo4bo$o4bobo4bo$o4bobo4bo2$2b3o3b3o!",
+    ),
+    Entry::new("Pentadecathlon", "x = 10, y = 3\n2bo4bo$2ob4ob2o$2bo4bo!"),
+    Entry::new(
+        "Gosper gun",
+        "x = 36, y = 9\n\
+         24bo$22bobo$12b2o6b2o12b2o$11bo3bo4b2o12b2o$2o8bo5bo3b2o$\
+         2o8bo3bob2o4bobo$10bo5bo7bo$11bo3bo$12b2o!",
+    ),
+    Entry::new("R-pentomino", "x = 3, y = 3\nb2o$2o$bo!"),
+    Entry::new("Acorn", "x = 7, y = 3\nbo$3bo$2o2b3o!"),
+    Entry::new("Diehard", "x = 8, y = 3\n6bo$2o$bo3b3o!"),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::analysis::{Cycle, CycleDetector};
+    use crate::format::rle;
+    use crate::game::World;
+
+    fn find(name: &str) -> &Pattern {
+        LIBRARY
+            .iter()
+            .find(|entry| entry.name == name)
+            .unwrap()
+            .pattern()
+    }
+
+    #[test]
+    fn all_patterns_parse() {
+        let expected = [
+            ("Glider", 3, 3, 5),
+            ("LWSS", 5, 4, 9),
+            ("MWSS", 6, 5, 11),
+            ("HWSS", 7, 5, 13),
+            ("Pulsar", 13, 13, 48),
+            ("Pentadecathlon", 10, 3, 12),
+            ("Gosper gun", 36, 9, 36),
+            ("R-pentomino", 3, 3, 5),
+            ("Acorn", 7, 3, 7),
+            ("Diehard", 8, 3, 7),
+        ];
+        for (entry, (name, width, height, population)) in LIBRARY.iter().zip(expected.iter()) {
+            let pattern = entry.pattern();
+
+            let parsed = rle::parse(entry.rle).unwrap();
+            assert_eq!(pattern, &parsed.pattern, "{}", name);
+
+            assert_eq!(entry.name, *name);
+            assert_eq!(
+                (pattern.width(), pattern.height(), pattern.population()),
+                (*width, *height, *population),
+                "{}",
+                name,
+            );
+        }
+    }
+
+    fn evolve(name: &str) -> Option<Cycle> {
+        let mut world = World::from_pattern(find(name));
+        let mut detector = CycleDetector::new();
+        let mut cycle = None;
+        for _ in 0..100 {
+            world.step();
+            cycle = cycle.or_else(|| detector.observe(&world));
+        }
+        cycle
+    }
+
+    #[test]
+    fn oscillators() {
+        for (name, period) in IntoIterator::into_iter([("Pulsar", 3), ("Pentadecathlon", 15)]) {
+            let actual = evolve(name);
+
+            assert_eq!(actual, Some(Cycle::Periodic(period)), "{}", name);
+        }
+    }
+
+    #[test]
+    fn spaceships() {
+        let expected = [
+            ("Glider", (1, 4)),
+            ("LWSS", (1, 2)),
+            ("MWSS", (1, 2)),
+            ("HWSS", (1, 2)),
+        ];
+        for (name, speed) in IntoIterator::into_iter(expected) {
+            let actual = match evolve(name) {
+                Some(Cycle::Moving(velocity)) => Some(velocity.speed()),
+                _ => None,
+            };
+
+            assert_eq!(actual, Some(speed), "{}", name);
+        }
+    }
+
+    #[test]
+    fn diehard_dies() {
+        let mut world = World::from_pattern(find("Diehard"));
+        for _ in 0..130 {
+            world.step();
+        }
+
+        assert_eq!(world.population(), 0);
+    }
+}
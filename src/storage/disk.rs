@@ -0,0 +1,51 @@
+// Copyright Claudio Mattera 2021.
+//
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Persistent disk
+
+use crate::wasm4::{diskr, diskw};
+
+/// The persistent storage of the cartridge
+///
+/// WASM-4 stores up to 1024 bytes per cartridge, which are always read and
+/// written from the beginning.
+#[derive(Clone, Copy)]
+pub struct Disk;
+
+impl Disk {
+    /// Maximal number of bytes on the disk
+    pub const SIZE: usize = 1024;
+
+    /// Read the disk into a buffer, returning the number of bytes read
+    pub fn read(&self, buffer: &mut [u8]) -> usize {
+        unsafe { diskr(buffer.as_mut_ptr(), buffer.len() as u32) as usize }
+    }
+
+    /// Write a buffer to the disk, returning the number of bytes written
+    pub fn write(&self, buffer: &[u8]) -> usize {
+        unsafe { diskw(buffer.as_ptr(), buffer.len() as u32) as usize }
+    }
+
+    /// Write a buffer to the disk at an offset, keeping the rest of the disk
+    ///
+    /// The whole disk is read into the image and written back, since WASM-4
+    /// can only write from the beginning.
+    /// The image is scratch space, and afterwards holds the new content of the
+    /// disk.
+    /// Return whether the buffer was written entirely.
+    pub fn write_at(&self, image: &mut [u8; Self::SIZE], offset: usize, buffer: &[u8]) -> bool {
+        let end = offset + buffer.len();
+        if end > Self::SIZE {
+            return false;
+        }
+        for byte in image.iter_mut() {
+            *byte = 0;
+        }
+        let size = self.read(image).max(end);
+        image[offset..end].copy_from_slice(buffer);
+        self.write(&image[..size]) == size
+    }
+}